@@ -1,4 +1,15 @@
-use bevy::prelude::*;
+use bevy::{
+    audio::Volume,
+    ecs::{entity::Entities, system::RunSystemOnce as _, world::Command},
+    prelude::*,
+};
+
+use crate::pool::{Pool, PoolApp, Release};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_pool::<SoundEffect>(|_| ());
+    app.add_systems(Update, recycle_sound_effects);
+}
 
 /// An organizational marker component that should be added to a spawned [`AudioBundle`] if it is in the
 /// general "music" category (ex: global background music, soundtrack, etc).
@@ -35,3 +46,50 @@ pub struct Music;
 /// ```
 #[derive(Component, Default)]
 pub struct SoundEffect;
+
+/// Play a sound effect once, on a pooled entity.
+pub struct PlaySoundEffect {
+    pub source: Handle<AudioSource>,
+    pub volume: f32,
+}
+
+impl PlaySoundEffect {
+    pub fn new(source: Handle<AudioSource>) -> Self {
+        Self {
+            source,
+            volume: 1.0,
+        }
+    }
+}
+
+impl Command for PlaySoundEffect {
+    fn apply(self, world: &mut World) {
+        world.run_system_once_with(self, play_sound_effect);
+    }
+}
+
+fn play_sound_effect(
+    In(sound): In<PlaySoundEffect>,
+    mut commands: Commands,
+    mut pool: ResMut<Pool<SoundEffect>>,
+    entities: &Entities,
+) {
+    pool.spawn(&mut commands, entities).insert((
+        AudioBundle {
+            source: sound.source,
+            // Only removes the audio, so the entity can be reused.
+            settings: PlaybackSettings::REMOVE.with_volume(Volume::new(sound.volume)),
+        },
+        SoundEffect,
+    ));
+}
+
+/// Sound effects that finished playing lost their audio source.
+fn recycle_sound_effects(
+    mut commands: Commands,
+    finished: Query<Entity, (With<SoundEffect>, Without<Handle<AudioSource>>)>,
+) {
+    for entity in &finished {
+        commands.add(Release::<SoundEffect>::new(entity));
+    }
+}
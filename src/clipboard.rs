@@ -0,0 +1,60 @@
+//! Copy text to the system clipboard.
+//!
+//! Native builds pipe the text into the clipboard tool of the platform, so no
+//! extra dependencies are needed. Web builds use the clipboard API of the
+//! browser.
+
+/// Copy `text` to the clipboard of the system.
+#[cfg(not(target_family = "wasm"))]
+pub fn copy(text: &str) -> Result<(), String> {
+    use std::io::Write as _;
+    use std::process::{Command, Stdio};
+
+    let tools: &[(&str, &[&str])] = if cfg!(target_os = "macos") {
+        &[("pbcopy", &[])]
+    } else if cfg!(target_os = "windows") {
+        &[("clip", &[])]
+    } else {
+        &[
+            ("wl-copy", &[]),
+            ("xclip", &["-selection", "clipboard"]),
+            ("xsel", &["--clipboard", "--input"]),
+        ]
+    };
+
+    for (program, args) in tools {
+        let Ok(mut child) = Command::new(program)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .spawn()
+        else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(text.as_bytes())
+                .map_err(|err| err.to_string())?;
+        }
+        let status = child.wait().map_err(|err| err.to_string())?;
+        if status.success() {
+            return Ok(());
+        }
+    }
+    Err("no clipboard tool found".to_string())
+}
+
+/// Copy `text` to the clipboard of the browser.
+///
+/// The browser copies in the background, so only a missing clipboard is
+/// reported. Other failures, like a denied permission, end up in the console.
+#[cfg(target_family = "wasm")]
+pub fn copy(text: &str) -> Result<(), String> {
+    let window = web_sys::window().ok_or("no browser window")?;
+    // Only available on pages served over HTTPS or from localhost.
+    let clipboard = window.navigator().clipboard();
+    if clipboard.is_undefined() {
+        return Err("the clipboard is not available on this page".to_string());
+    }
+    let _ = clipboard.write_text(text);
+    Ok(())
+}
@@ -0,0 +1,280 @@
+//! Options for the native binary, read from the command line.
+//!
+//! Run with `--help` to list them. Web builds have no command line and always
+//! use the defaults.
+
+use bevy::prelude::*;
+
+use crate::playfield::{PlayfieldScaling, PLAYFIELD_SIZE};
+
+const USAGE: &str = "\
+Usage: ludum-dare56 [OPTIONS]
+
+Options:
+  --window-size <WxH>    Size of the window, e.g. 1280x720
+  --fullscreen           Start in fullscreen
+  --scaling <MODE>       How the playfield fits the window: letterbox, fit or expand
+  --mute                 Start without sound
+  --level <PATH>         Play only this level file, relative to assets/
+  --campaign <PATH>      Play the levels listed in this RON file, relative to assets/
+  --start-wave <N>       Start the campaign at wave N
+  --seed <N>             Seed for placing creatures and generating waves
+  --windowed-dev         Start in a window on the dev screen
+  --headless-bench       Time the gameplay systems without a window or renderer
+                         and print the results as JSON
+  --bench-creatures <N>  Creatures in the benchmark, 500 by default
+  --bench-frames <N>     Frames the benchmark runs for, 600 by default
+  --bench-output <PATH>  Also write the benchmark results to this file
+  --help                 Print this message";
+
+/// Options the app was started with.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct AppConfig {
+    pub window_size: Vec2,
+    pub fullscreen: bool,
+    pub scaling: PlayfieldScaling,
+    pub mute: bool,
+    /// A level file to play instead of the campaign.
+    pub level: Option<String>,
+    /// A RON file with a list of level files to play as the campaign.
+    pub campaign: Option<String>,
+    /// The wave the campaign starts at, counted from 1.
+    pub start_wave: Option<u32>,
+    /// Seed used instead of random seeds for placing creatures and generating
+    /// waves. Bullets and particles stay random.
+    pub seed: Option<u64>,
+    /// Go to the dev screen after loading, and never start in fullscreen.
+    pub windowed_dev: bool,
+    /// Run the benchmark in [`bench`](crate::bench) instead of the game,
+    /// without a window and without rendering.
+    pub headless_bench: bool,
+    /// Creatures spawned for the benchmark.
+    pub bench_creatures: usize,
+    /// Frames the benchmark runs for, after warming up.
+    pub bench_frames: usize,
+    /// A file to write the benchmark results to.
+    pub bench_output: Option<String>,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            window_size: PLAYFIELD_SIZE,
+            fullscreen: false,
+            scaling: PlayfieldScaling::default(),
+            mute: false,
+            level: None,
+            campaign: None,
+            start_wave: None,
+            seed: None,
+            windowed_dev: false,
+            headless_bench: false,
+            bench_creatures: 500,
+            bench_frames: 600,
+            bench_output: None,
+        }
+    }
+}
+
+impl AppConfig {
+    /// Read the options from the command line. Exits the process with the
+    /// usage message if they are invalid.
+    pub fn from_args() -> Self {
+        match Self::parse(std::env::args().skip(1)) {
+            Ok(Some(config)) => config,
+            Ok(None) => {
+                println!("{USAGE}");
+                std::process::exit(0);
+            }
+            Err(err) => {
+                eprintln!("error: {err}\n\n{USAGE}");
+                std::process::exit(2);
+            }
+        }
+    }
+
+    /// Parse the options from `args`, without the name of the program. Returns
+    /// `None` if the help was requested.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut config = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("missing value for {arg}"))
+            };
+            match arg.as_str() {
+                "--window-size" => {
+                    let value = value()?;
+                    config.window_size = value
+                        .split_once('x')
+                        .and_then(|(width, height)| {
+                            Some(Vec2::new(width.parse().ok()?, height.parse().ok()?))
+                        })
+                        .filter(|size| size.x > 0.0 && size.y > 0.0)
+                        .ok_or_else(|| {
+                            format!("invalid window size '{value}', expected e.g. 1280x720")
+                        })?;
+                }
+                "--fullscreen" => config.fullscreen = true,
+                "--scaling" => {
+                    let value = value()?;
+                    config.scaling = PlayfieldScaling::parse(&value)
+                        .ok_or_else(|| format!("unknown scaling mode '{value}'"))?;
+                }
+                "--mute" => config.mute = true,
+                "--level" => config.level = Some(value()?),
+                "--campaign" => config.campaign = Some(value()?),
+                "--start-wave" => {
+                    let value = value()?;
+                    let wave = value
+                        .parse()
+                        .ok()
+                        .filter(|wave| *wave > 0)
+                        .ok_or_else(|| format!("invalid wave '{value}', waves start at 1"))?;
+                    config.start_wave = Some(wave);
+                }
+                "--seed" => {
+                    let value = value()?;
+                    let seed = value
+                        .parse()
+                        .map_err(|_| format!("invalid seed '{value}'"))?;
+                    config.seed = Some(seed);
+                }
+                "--windowed-dev" => config.windowed_dev = true,
+                "--headless-bench" => config.headless_bench = true,
+                "--bench-creatures" => {
+                    let value = value()?;
+                    config.bench_creatures = value
+                        .parse()
+                        .map_err(|_| format!("invalid number of creatures '{value}'"))?;
+                }
+                "--bench-frames" => {
+                    let value = value()?;
+                    config.bench_frames = value
+                        .parse()
+                        .ok()
+                        .filter(|frames| *frames > 0)
+                        .ok_or_else(|| format!("invalid number of frames '{value}'"))?;
+                }
+                "--bench-output" => config.bench_output = Some(value()?),
+                "--help" | "-h" => return Ok(None),
+                _ => return Err(format!("unknown option '{arg}'")),
+            }
+        }
+        if config.level.is_some() && config.campaign.is_some() {
+            return Err("--level and --campaign can't be used together".to_string());
+        }
+        Ok(Some(config))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<AppConfig>, String> {
+        AppConfig::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn parse_ok(args: &[&str]) -> AppConfig {
+        parse(args).unwrap().unwrap()
+    }
+
+    #[test]
+    fn no_options_are_the_defaults() {
+        assert_eq!(parse_ok(&[]), AppConfig::default());
+    }
+
+    #[test]
+    fn help_returns_none() {
+        assert_eq!(parse(&["--help"]), Ok(None));
+        assert_eq!(parse(&["--mute", "-h"]), Ok(None));
+    }
+
+    #[test]
+    fn window_size() {
+        let config = parse_ok(&["--window-size", "800x600"]);
+        assert_eq!(config.window_size, Vec2::new(800.0, 600.0));
+
+        for value in ["800", "800x", "x600", "0x600", "800x-1", "axb"] {
+            let err = parse(&["--window-size", value]).unwrap_err();
+            assert!(err.contains("invalid window size"), "{value}: {err}");
+        }
+    }
+
+    #[test]
+    fn scaling() {
+        let config = parse_ok(&["--scaling", "expand"]);
+        assert_eq!(config.scaling, PlayfieldScaling::Expand);
+
+        let err = parse(&["--scaling", "stretch"]).unwrap_err();
+        assert_eq!(err, "unknown scaling mode 'stretch'");
+    }
+
+    #[test]
+    fn flags() {
+        let config = parse_ok(&["--fullscreen", "--mute", "--windowed-dev"]);
+        assert!(config.fullscreen);
+        assert!(config.mute);
+        assert!(config.windowed_dev);
+    }
+
+    #[test]
+    fn start_wave() {
+        assert_eq!(parse_ok(&["--start-wave", "3"]).start_wave, Some(3));
+
+        for value in ["0", "-1", "three"] {
+            let err = parse(&["--start-wave", value]).unwrap_err();
+            assert!(err.contains("waves start at 1"), "{value}: {err}");
+        }
+    }
+
+    #[test]
+    fn seed() {
+        assert_eq!(parse_ok(&["--seed", "42"]).seed, Some(42));
+        assert_eq!(parse(&["--seed", "-1"]).unwrap_err(), "invalid seed '-1'");
+    }
+
+    #[test]
+    fn bench() {
+        let config = parse_ok(&[
+            "--headless-bench",
+            "--bench-creatures",
+            "2000",
+            "--bench-frames",
+            "100",
+            "--bench-output",
+            "bench.json",
+        ]);
+        assert!(config.headless_bench);
+        assert_eq!(config.bench_creatures, 2000);
+        assert_eq!(config.bench_frames, 100);
+        assert_eq!(config.bench_output.as_deref(), Some("bench.json"));
+
+        assert!(parse(&["--bench-creatures", "many"]).is_err());
+        assert!(parse(&["--bench-frames", "0"]).is_err());
+    }
+
+    #[test]
+    fn level_and_campaign() {
+        assert_eq!(
+            parse_ok(&["--level", "levels/1.ron"]).level.as_deref(),
+            Some("levels/1.ron")
+        );
+        assert_eq!(
+            parse_ok(&["--campaign", "custom.ron"]).campaign.as_deref(),
+            Some("custom.ron")
+        );
+        assert_eq!(
+            parse(&["--level", "levels/1.ron", "--campaign", "custom.ron"]).unwrap_err(),
+            "--level and --campaign can't be used together"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse(&["--seed"]).unwrap_err(), "missing value for --seed");
+        assert_eq!(parse(&["--fast"]).unwrap_err(), "unknown option '--fast'");
+    }
+}
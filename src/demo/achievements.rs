@@ -0,0 +1,315 @@
+//! Local achievements.
+//!
+//! The achievements are defined in `assets/achievements.ron`. They are checked
+//! against gameplay events, and unlocked ones are kept in the [`SaveData`].
+
+use std::path::Path;
+
+use bevy::prelude::*;
+use bevy_common_assets::ron::RonAssetPlugin;
+
+use crate::{
+    asset_tracking::LoadResource,
+    config::AppConfig,
+    save::SaveData,
+    screens::{GameScore, Screen},
+    theme::palette::{THEME_VANILLA, THEME_VANILLA_DARK},
+    AppSet,
+};
+
+use super::{
+    events::{BulletLanded, GameOver, GameOverReason, WaveCleared, WaveStarted},
+    level::GameMode,
+};
+
+/// How long an unlocked achievement is announced.
+const TOAST_DURATION_SECS: f32 = 4.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(RonAssetPlugin::<AchievementList>::new(&[
+        "achievements.ron",
+    ]));
+    app.load_resource::<AchievementAssets>();
+    app.add_event::<AchievementUnlocked>();
+    app.init_resource::<AchievementTracker>();
+    app.add_systems(Startup, spawn_toast_container);
+    app.add_systems(OnEnter(Screen::Gameplay), reset_tracker);
+    app.add_systems(
+        Update,
+        // A wave is cleared in the same frame as the next one starts, so
+        // check it before the tracker moves on to the next wave.
+        (
+            track_bullets,
+            check_cleared_waves,
+            track_waves,
+            check_campaign,
+        )
+            .chain()
+            .after(AppSet::Update)
+            .run_if(
+                in_state(Screen::Gameplay)
+                    .and_then(resource_exists::<AchievementAssets>)
+                    .and_then(|game_score: Res<GameScore>| !game_score.console_used),
+            ),
+    );
+    app.add_systems(Update, (spawn_toasts, remove_toasts));
+}
+
+/// All achievements there are.
+#[derive(Debug, Clone, Asset, Reflect, serde::Deserialize)]
+pub struct AchievementList {
+    pub achievements: Vec<Achievement>,
+}
+
+#[derive(Debug, Clone, Reflect, serde::Deserialize)]
+pub struct Achievement {
+    /// Identifies the achievement in the save data. Don't change it once the
+    /// achievement was released.
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub condition: AchievementCondition,
+}
+
+/// What needs to be done to unlock an achievement.
+#[derive(Debug, Clone, Reflect, serde::Deserialize)]
+pub enum AchievementCondition {
+    /// Clear a wave without a single miss.
+    CleanWave,
+    /// Hit at least this many creatures with one bullet.
+    MultiKill(u32),
+    /// Clear the level file `level` with at least `seconds` left.
+    TimeLeft { level: String, seconds: f32 },
+    /// Clear all waves of the campaign, starting from the first one.
+    FinishCampaign,
+}
+
+#[derive(Resource, Asset, Reflect, Clone)]
+pub struct AchievementAssets {
+    #[dependency]
+    pub list: Handle<AchievementList>,
+}
+
+impl FromWorld for AchievementAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            list: assets.load("achievements.ron"),
+        }
+    }
+}
+
+/// An achievement was unlocked for the first time.
+#[derive(Event, Debug, Clone)]
+pub struct AchievementUnlocked {
+    pub name: String,
+}
+
+/// What happened so far in the current game, as far as achievements care.
+#[derive(Resource, Debug, Clone, Default, Reflect)]
+struct AchievementTracker {
+    /// The first wave that was played.
+    first_wave: Option<u32>,
+    /// Misses since the current wave started.
+    misses_in_wave: u32,
+}
+
+#[derive(Component, Debug, Clone, Reflect)]
+struct ToastContainer;
+
+#[derive(Component, Debug, Clone, Reflect)]
+struct Toast {
+    timer: Timer,
+}
+
+/// Unlock all achievements with a condition that `is_met`.
+fn unlock(
+    list: &AchievementList,
+    save: &mut SaveData,
+    events: &mut EventWriter<AchievementUnlocked>,
+    is_met: impl Fn(&AchievementCondition) -> bool,
+) {
+    let mut changed = false;
+    for achievement in &list.achievements {
+        if is_met(&achievement.condition) && save.achievements.insert(achievement.id.clone()) {
+            info!("Unlocked achievement {:?}", achievement.id);
+            events.send(AchievementUnlocked {
+                name: achievement.name.clone(),
+            });
+            changed = true;
+        }
+    }
+    if changed {
+        save.save();
+    }
+}
+
+fn reset_tracker(mut tracker: ResMut<AchievementTracker>) {
+    *tracker = AchievementTracker::default();
+}
+
+fn track_waves(mut events: EventReader<WaveStarted>, mut tracker: ResMut<AchievementTracker>) {
+    if let Some(event) = events.read().last() {
+        tracker.first_wave.get_or_insert(event.wave);
+        tracker.misses_in_wave = 0;
+    }
+}
+
+fn track_bullets(
+    mut events: EventReader<BulletLanded>,
+    mut tracker: ResMut<AchievementTracker>,
+    assets: Res<AchievementAssets>,
+    lists: Res<Assets<AchievementList>>,
+    mut save: ResMut<SaveData>,
+    mut unlocked: EventWriter<AchievementUnlocked>,
+) {
+    let Some(list) = lists.get(&assets.list) else {
+        return;
+    };
+    for landing in events.read() {
+        if landing.missed() {
+            tracker.misses_in_wave += 1;
+        }
+        unlock(
+            list,
+            &mut save,
+            &mut unlocked,
+            |condition| match condition {
+                AchievementCondition::MultiKill(count) => landing.hits >= *count,
+                _ => false,
+            },
+        );
+    }
+}
+
+/// Check the conditions for a cleared wave.
+fn check_cleared_waves(
+    mut events: EventReader<WaveCleared>,
+    tracker: Res<AchievementTracker>,
+    assets: Res<AchievementAssets>,
+    lists: Res<Assets<AchievementList>>,
+    mut save: ResMut<SaveData>,
+    mut unlocked: EventWriter<AchievementUnlocked>,
+) {
+    let Some(list) = lists.get(&assets.list) else {
+        return;
+    };
+    for event in events.read() {
+        let time_left = event.time_left.as_secs_f32();
+        let level_path = event.level.path().map(|path| path.path());
+        unlock(
+            list,
+            &mut save,
+            &mut unlocked,
+            |condition| match condition {
+                AchievementCondition::CleanWave => tracker.misses_in_wave == 0,
+                AchievementCondition::TimeLeft { level, seconds } => {
+                    level_path == Some(Path::new(level)) && time_left >= *seconds
+                }
+                _ => false,
+            },
+        );
+    }
+}
+
+fn check_campaign(
+    mut events: EventReader<GameOver>,
+    tracker: Res<AchievementTracker>,
+    mode: Res<GameMode>,
+    config: Res<AppConfig>,
+    assets: Res<AchievementAssets>,
+    lists: Res<Assets<AchievementList>>,
+    mut save: ResMut<SaveData>,
+    mut unlocked: EventWriter<AchievementUnlocked>,
+) {
+    let won = events
+        .read()
+        .any(|event| event.reason == GameOverReason::Won);
+    // Custom levels and campaigns don't count.
+    let full_campaign = *mode == GameMode::Campaign
+        && tracker.first_wave == Some(1)
+        && config.level.is_none()
+        && config.campaign.is_none();
+    if !won || !full_campaign {
+        return;
+    }
+    let Some(list) = lists.get(&assets.list) else {
+        return;
+    };
+    unlock(list, &mut save, &mut unlocked, |condition| {
+        matches!(condition, AchievementCondition::FinishCampaign)
+    });
+}
+
+fn spawn_toast_container(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Toasts"),
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(10.0),
+                bottom: Val::Px(10.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            z_index: ZIndex::Global(100),
+            ..default()
+        },
+        ToastContainer,
+    ));
+}
+
+fn spawn_toasts(
+    mut events: EventReader<AchievementUnlocked>,
+    mut commands: Commands,
+    container_query: Query<Entity, With<ToastContainer>>,
+) {
+    let Ok(container) = container_query.get_single() else {
+        return;
+    };
+    for event in events.read() {
+        commands.entity(container).with_children(|children| {
+            children
+                .spawn((
+                    Name::new("Toast"),
+                    NodeBundle {
+                        style: Style {
+                            padding: UiRect::all(Val::Px(10.0)),
+                            border: UiRect::all(Val::Px(4.0)),
+                            ..default()
+                        },
+                        background_color: BackgroundColor(THEME_VANILLA),
+                        border_color: BorderColor(THEME_VANILLA_DARK),
+                        ..default()
+                    },
+                    Toast {
+                        timer: Timer::from_seconds(TOAST_DURATION_SECS, TimerMode::Once),
+                    },
+                ))
+                .with_children(|toast| {
+                    toast.spawn(TextBundle::from_section(
+                        format!("Achievement unlocked: {}", event.name),
+                        TextStyle {
+                            font_size: 24.0,
+                            color: Color::BLACK,
+                            ..default()
+                        },
+                    ));
+                });
+        });
+    }
+}
+
+/// Toasts run on real time, so they go away while the game is paused.
+fn remove_toasts(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut toast_query: Query<(Entity, &mut Toast)>,
+) {
+    for (entity, mut toast) in &mut toast_query {
+        if toast.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
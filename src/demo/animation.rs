@@ -53,6 +53,11 @@ impl CreatureAnimation {
         }
     }
 
+    /// The total duration of the animation.
+    pub fn duration(&self) -> Duration {
+        self.timer.duration()
+    }
+
     /// Update animation timers.
     pub fn update_timer(&mut self, delta: Duration) {
         self.timer.tick(delta);
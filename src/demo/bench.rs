@@ -0,0 +1,516 @@
+//! Benchmark of the gameplay systems, run with `--headless-bench`.
+//!
+//! Spawns [`AppConfig::bench_creatures`] creatures with [`SpawnCreature`],
+//! mixing all movement patterns and both edge behaviors, in an app without a
+//! window or renderer. Every frame, a few bullets land on random positions.
+//! The movement and hit detection systems are timed one by one.
+//!
+//! Then the same creatures churn: every frame, some of them die and as many
+//! are spawned, once with the creature [`Pool`] and once despawning them
+//! instead. The frame time of the pooled run has to stay flat over the run,
+//! or the benchmark fails.
+//!
+//! Last, the [`SpatialGrid`] finds the creatures under bullets and around
+//! explosions for a growing number of creatures, compared to checking every
+//! creature. Both have to find the same creatures.
+//!
+//! The results are printed as JSON, and written to
+//! [`AppConfig::bench_output`] if it is set. Time advances by a fixed step per
+//! frame, so runs with the same `--seed` simulate the same game.
+
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    ecs::{system::BoxedSystem, world::Command},
+    prelude::*,
+    time::TimeUpdateStrategy,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    asset_tracking,
+    config::AppConfig,
+    playfield::PLAYFIELD_SIZE,
+    pool::{Pool, PoolApp, Release},
+    save::SaveData,
+    screens::GameplayArea,
+};
+
+use super::{
+    creature::{
+        process_bullets_landing, reset_creature, Bullet, Creature, CreatureAssets, SpawnCreature,
+    },
+    creature_image::CreatureImage,
+    difficulty::Difficulty,
+    events::{BulletLanded, CreatureHit},
+    juice::EffectSettings,
+    movement::{apply_movement, apply_screen_bounce, apply_screen_wrap, MovementController},
+    movement_pattern::{move_creatures, update_timer, MovementPatternDefinition},
+    particles,
+    players::PlayerId,
+    spatial::{update_spatial_grid, SpatialGrid},
+};
+
+/// Simulated time per frame.
+const FRAME_TIME: Duration = Duration::from_nanos(16_666_667);
+/// Frames that are run before measuring, while systems and caches settle.
+const WARMUP_FRAMES: usize = 60;
+/// Bullets that land every frame.
+const BULLETS_PER_FRAME: usize = 8;
+/// Frames each creature lives while churning.
+const CREATURE_LIFETIME: usize = 30;
+/// Part of the measured frames at the start and the end of a churn run that
+/// are compared.
+const CHURN_WINDOW: f64 = 0.2;
+/// How much slower the end of a pooled churn run may be than its start.
+const MAX_CHURN_GROWTH: f64 = 1.25;
+/// Numbers of creatures the spatial grid is measured with.
+const GRID_CREATURES: [usize; 3] = [100, 1_000, 10_000];
+/// Size of a creature hitbox in the spatial grid comparison.
+const GRID_HITBOX: Vec2 = Vec2::splat(96.0);
+/// Point and circle queries of the spatial grid comparison.
+const GRID_QUERIES: usize = 1000;
+const GRID_EXPLOSION_RADIUS: f32 = 150.0;
+
+/// Run the benchmark and report the results.
+pub fn run(config: &AppConfig) -> AppExit {
+    let report = BenchReport {
+        creatures: config.bench_creatures,
+        frames: config.bench_frames,
+        bullets_per_frame: BULLETS_PER_FRAME,
+        systems: measure_systems(config),
+        churn: ChurnReport {
+            pooled: measure_churn(config, true),
+            unpooled: measure_churn(config, false),
+        },
+        spatial_grid: GRID_CREATURES
+            .into_iter()
+            .map(|creatures| measure_spatial_grid(config, creatures))
+            .collect(),
+    };
+    let json = match serde_json::to_string_pretty(&report) {
+        Ok(json) => json,
+        Err(err) => {
+            eprintln!("error: couldn't serialize the results: {err}");
+            return AppExit::error();
+        }
+    };
+    println!("{json}");
+    if let Some(path) = &config.bench_output {
+        if let Err(err) = std::fs::write(path, &json) {
+            eprintln!("error: couldn't write the results to {path}: {err}");
+            return AppExit::error();
+        }
+    }
+    if !report.churn.pooled.flat {
+        eprintln!(
+            "error: the frame time grew by {:.0}% while churning creatures with the pool",
+            (report.churn.pooled.growth - 1.0) * 100.0
+        );
+        return AppExit::error();
+    }
+    if let Some(timings) = report
+        .spatial_grid
+        .iter()
+        .find(|timings| timings.grid_found != timings.scan_found)
+    {
+        eprintln!(
+            "error: the spatial grid found {} creatures instead of {} with {} creatures",
+            timings.grid_found, timings.scan_found, timings.creatures
+        );
+        return AppExit::error();
+    }
+    AppExit::Success
+}
+
+/// The results of a benchmark run.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BenchReport {
+    pub creatures: usize,
+    pub frames: usize,
+    pub bullets_per_frame: usize,
+    pub systems: SystemsReport,
+    pub churn: ChurnReport,
+    pub spatial_grid: Vec<GridTimings>,
+}
+
+/// Timings of the gameplay systems with a fixed set of creatures.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SystemsReport {
+    /// Timings of the whole frame, including Bevy's own systems.
+    pub frame: Timings,
+    /// Timings of each measured system, by name.
+    pub systems: BTreeMap<&'static str, Timings>,
+}
+
+/// Frame times while creatures die and are replaced, with and without the
+/// pool.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ChurnReport {
+    pub pooled: ChurnTimings,
+    pub unpooled: ChurnTimings,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ChurnTimings {
+    pub frame: Timings,
+    /// Mean frame time at the start of the run, in microseconds.
+    pub start_us: f64,
+    /// Mean frame time at the end of the run, in microseconds.
+    pub end_us: f64,
+    /// `end_us` divided by `start_us`.
+    pub growth: f64,
+    /// Whether the growth stayed below the allowed maximum.
+    pub flat: bool,
+    /// Creature entities that were spawned.
+    pub spawned: u64,
+    /// Creature entities that were taken from the pool.
+    pub reused: u64,
+}
+
+/// Finding creatures with the [`SpatialGrid`] and by checking every creature,
+/// in microseconds.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct GridTimings {
+    pub creatures: usize,
+    /// Inserting all creatures into the grid.
+    pub build_us: f64,
+    pub grid_us: f64,
+    pub scan_us: f64,
+    /// Creatures found by all queries of the grid.
+    pub grid_found: usize,
+    /// Creatures found by all queries of the scan.
+    pub scan_found: usize,
+}
+
+/// Statistics over the measured frames, in microseconds.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Timings {
+    pub mean_us: f64,
+    pub median_us: f64,
+    pub p95_us: f64,
+    pub max_us: f64,
+    pub total_us: f64,
+}
+
+impl Timings {
+    fn new(samples: &[Duration]) -> Self {
+        let mut micros: Vec<f64> = samples
+            .iter()
+            .map(|duration| duration.as_secs_f64() * 1e6)
+            .collect();
+        micros.sort_by(f64::total_cmp);
+        let percentile = |p: f64| {
+            let index = (micros.len().saturating_sub(1) as f64 * p).round() as usize;
+            micros.get(index).copied().unwrap_or_default()
+        };
+        let total_us: f64 = micros.iter().sum();
+        Self {
+            mean_us: total_us / micros.len().max(1) as f64,
+            median_us: percentile(0.5),
+            p95_us: percentile(0.95),
+            max_us: micros.last().copied().unwrap_or_default(),
+            total_us,
+        }
+    }
+}
+
+/// The time each measured system took, per frame.
+#[derive(Resource, Debug, Default)]
+struct SystemTimes(BTreeMap<&'static str, Vec<Duration>>);
+
+#[derive(Resource)]
+struct BenchRng(StdRng);
+
+/// The number of creatures to keep alive.
+#[derive(Resource)]
+struct CreatureCount(usize);
+
+/// Whether dead creatures go back into the pool while churning.
+#[derive(Resource)]
+struct Churn {
+    pooled: bool,
+}
+
+/// An app with the gameplay systems, the measured ones timed, and all
+/// creatures spawned.
+fn bench_app(config: &AppConfig) -> App {
+    let mut app = App::new();
+    crate::add_bevy_plugins(
+        &mut app,
+        &AppConfig {
+            headless_bench: true,
+            mute: true,
+            ..config.clone()
+        },
+    );
+    // Landing bullets emit dust, so the particle pool has to exist.
+    app.add_plugins((asset_tracking::plugin, particles::plugin));
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(FRAME_TIME));
+    app.insert_resource(GameplayArea {
+        main_area: Rect::from_center_size(Vec2::ZERO, PLAYFIELD_SIZE),
+    });
+    app.insert_resource(SaveData {
+        effects: EffectSettings {
+            particles: false,
+            ..default()
+        },
+        ..default()
+    });
+    app.init_resource::<Difficulty>();
+    app.init_resource::<SpatialGrid>();
+    app.init_resource::<CreatureAssets>();
+    app.init_resource::<SystemTimes>();
+    app.insert_resource(BenchRng(StdRng::seed_from_u64(config.seed.unwrap_or(0))));
+    app.insert_resource(CreatureCount(config.bench_creatures));
+    app.init_pool::<Creature>(reset_creature);
+    app.add_event::<BulletLanded>();
+    app.add_event::<CreatureHit>();
+    // The same order as in the game.
+    app.add_systems(
+        Update,
+        (
+            (kill_creatures, replace_creatures)
+                .chain()
+                .run_if(resource_exists::<Churn>),
+            update_timer,
+            land_bullets,
+            timed("move_creatures", move_creatures),
+            timed("apply_movement", apply_movement),
+            timed("apply_screen_wrap", apply_screen_wrap),
+            timed("apply_screen_bounce", apply_screen_bounce),
+            timed("update_spatial_grid", update_spatial_grid),
+            timed("process_bullets_landing", process_bullets_landing),
+        )
+            .chain(),
+    );
+    app.finish();
+    app.cleanup();
+
+    replace_creatures(app.world_mut());
+    for _ in 0..WARMUP_FRAMES {
+        app.update();
+    }
+    app.world_mut().resource_mut::<SystemTimes>().0.clear();
+    app
+}
+
+/// Run `app` for the configured number of frames and return their times.
+fn run_frames(app: &mut App, config: &AppConfig) -> Vec<Duration> {
+    (0..config.bench_frames)
+        .map(|_| {
+            let start = Instant::now();
+            app.update();
+            start.elapsed()
+        })
+        .collect()
+}
+
+fn measure_systems(config: &AppConfig) -> SystemsReport {
+    let mut app = bench_app(config);
+    let frames = run_frames(&mut app, config);
+    let times = app.world_mut().remove_resource::<SystemTimes>();
+    SystemsReport {
+        frame: Timings::new(&frames),
+        systems: times
+            .unwrap_or_default()
+            .0
+            .into_iter()
+            .map(|(name, samples)| (name, Timings::new(&samples)))
+            .collect(),
+    }
+}
+
+fn measure_churn(config: &AppConfig, pooled: bool) -> ChurnTimings {
+    let mut app = bench_app(config);
+    app.insert_resource(Churn { pooled });
+    let frames = run_frames(&mut app, config);
+
+    let window = ((frames.len() as f64 * CHURN_WINDOW) as usize).max(1);
+    let mean = |frames: &[Duration]| Timings::new(frames).mean_us;
+    let start_us = mean(&frames[..window]);
+    let end_us = mean(&frames[frames.len() - window..]);
+    let growth = end_us / start_us.max(f64::EPSILON);
+    let pool = app.world().resource::<Pool<Creature>>();
+    ChurnTimings {
+        frame: Timings::new(&frames),
+        start_us,
+        end_us,
+        growth,
+        flat: growth <= MAX_CHURN_GROWTH,
+        spawned: pool.spawned,
+        reused: pool.reused,
+    }
+}
+
+fn measure_spatial_grid(config: &AppConfig, creatures: usize) -> GridTimings {
+    let rng = &mut StdRng::seed_from_u64(config.seed.unwrap_or(0));
+    let area = Rect::from_center_size(Vec2::ZERO, PLAYFIELD_SIZE);
+    let hitboxes: Vec<(Entity, Rect)> = (0..creatures)
+        .map(|i| {
+            let center = random_point(rng, area);
+            let rect = Rect::from_center_size(center, GRID_HITBOX);
+            (Entity::from_raw(i as u32), rect)
+        })
+        .collect();
+    let points: Vec<Vec2> = (0..GRID_QUERIES).map(|_| random_point(rng, area)).collect();
+
+    let start = Instant::now();
+    let mut grid = SpatialGrid::new(area);
+    for (entity, rect) in &hitboxes {
+        grid.insert(*entity, *rect);
+    }
+    let build = start.elapsed();
+
+    let start = Instant::now();
+    let grid_found = points
+        .iter()
+        .map(|point| {
+            grid.query_point(*point).count()
+                + grid.query_circle(*point, GRID_EXPLOSION_RADIUS).count()
+        })
+        .sum();
+    let grid_time = start.elapsed();
+
+    let start = Instant::now();
+    let scan_found = points
+        .iter()
+        .map(|point| {
+            let hits = hitboxes.iter().filter(|(_, rect)| rect.contains(*point));
+            let near = hitboxes.iter().filter(|(_, rect)| {
+                point.clamp(rect.min, rect.max).distance(*point) <= GRID_EXPLOSION_RADIUS
+            });
+            hits.count() + near.count()
+        })
+        .sum();
+    let scan_time = start.elapsed();
+
+    let micros = |duration: Duration| duration.as_secs_f64() * 1e6;
+    GridTimings {
+        creatures,
+        build_us: micros(build),
+        grid_us: micros(grid_time),
+        scan_us: micros(scan_time),
+        grid_found,
+        scan_found,
+    }
+}
+
+/// Run `system` as part of an exclusive system that records how long it took,
+/// including applying its commands.
+fn timed<M>(
+    name: &'static str,
+    system: impl IntoSystem<(), (), M>,
+) -> impl FnMut(&mut World) + Send + Sync + 'static {
+    let mut system: BoxedSystem = Box::new(IntoSystem::into_system(system));
+    let mut initialized = false;
+    move |world: &mut World| {
+        if !initialized {
+            system.initialize(world);
+            initialized = true;
+        }
+        let start = Instant::now();
+        system.run((), world);
+        let elapsed = start.elapsed();
+        world
+            .resource_mut::<SystemTimes>()
+            .0
+            .entry(name)
+            .or_default()
+            .push(elapsed);
+    }
+}
+
+/// Kill the creatures that reached the end of their lifetime. Creatures
+/// don't age, so this takes as many as would die on average.
+fn kill_creatures(
+    mut commands: Commands,
+    churn: Res<Churn>,
+    creatures: Query<Entity, With<Creature>>,
+) {
+    let deaths = creatures.iter().len().div_ceil(CREATURE_LIFETIME);
+    for entity in creatures.iter().take(deaths) {
+        if churn.pooled {
+            commands.add(Release::<Creature>::new(entity));
+        } else {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Spawn creatures with [`SpawnCreature`] until there are as many as
+/// configured.
+fn replace_creatures(world: &mut World) {
+    let alive = world
+        .query_filtered::<(), With<Creature>>()
+        .iter(world)
+        .len();
+    let count = world.resource::<CreatureCount>().0;
+    let area = world.resource::<GameplayArea>().main_area;
+    let mut rng = world.resource_mut::<BenchRng>().0.clone();
+    for i in alive..count {
+        let movement = match i % 3 {
+            0 => MovementPatternDefinition::Constant {
+                speed: Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU)),
+            },
+            1 => MovementPatternDefinition::Periodic {
+                duration_ms: rng.gen_range(1000..4000),
+                max_speed: Vec2::new(rng.gen_range(-1.0..=1.0), rng.gen_range(-1.0..=1.0)),
+            },
+            _ => MovementPatternDefinition::Circle {
+                duration_ms: rng.gen_range(2000..6000),
+                radius: rng.gen_range(0.5..=1.5),
+            },
+        };
+        SpawnCreature {
+            image: CreatureImage::ALL[i % CreatureImage::ALL.len()],
+            max_speed: rng.gen_range(100.0..=300.0),
+            pos: random_point(&mut rng, area),
+            movement: movement.build(),
+            shrink_duration: Duration::from_secs(3600),
+            // Switch every three creatures, so every pattern gets both.
+            wrap: i / 3 % 2 == 0,
+            scale: 1.0,
+            mass: 1.0,
+            radius: None,
+            on_death: Vec::new(),
+        }
+        .apply(world);
+    }
+    world.resource_mut::<BenchRng>().0 = rng;
+}
+
+fn random_point(rng: &mut StdRng, area: Rect) -> Vec2 {
+    Vec2::new(
+        rng.gen_range(area.min.x..area.max.x),
+        rng.gen_range(area.min.y..area.max.y),
+    )
+}
+
+/// Replace last frame's bullets with new ones that land right away. Hit
+/// creatures are not killed, so the number of creatures stays the same.
+fn land_bullets(
+    mut commands: Commands,
+    mut rng: ResMut<BenchRng>,
+    gameplay_area: Res<GameplayArea>,
+    bullets: Query<Entity, With<Bullet>>,
+) {
+    for entity in &bullets {
+        commands.entity(entity).despawn();
+    }
+    for _ in 0..BULLETS_PER_FRAME {
+        let target = random_point(&mut rng.0, gameplay_area.main_area);
+        let mut timer = Timer::new(Duration::ZERO, TimerMode::Once);
+        timer.tick(Duration::ZERO);
+        commands.spawn((
+            Name::new("Bullet"),
+            Bullet { timer, target },
+            PlayerId(0),
+            SpatialBundle::from_transform(Transform::from_translation(target.extend(2.0))),
+            MovementController::default(),
+        ));
+    }
+}
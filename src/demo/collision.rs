@@ -0,0 +1,137 @@
+//! Soft collisions between creatures, in levels with
+//! [`LevelDefinition::collisions`] turned on.
+//!
+//! Every creature is a circle with a mass, see [`CreatureBody`]. Overlapping
+//! creatures are pushed apart through [`MovementController::push`], the
+//! lighter one more than the heavier one. The push grows with the overlap,
+//! so piles spread out over a few frames instead of jumping apart.
+
+use bevy::prelude::*;
+
+use crate::AppSet;
+
+use super::{
+    creature::{Creature, DeathAnimation},
+    creature_image::CreatureImage,
+    level::{ActiveLevel, LevelDefinition},
+    movement::{apply_movement, MovementController, MovementFrozen},
+    movement_pattern::move_creatures,
+    spatial::SpatialGrid,
+};
+
+/// Push per world unit of overlap, in world units per second.
+const STIFFNESS: f32 = 6.0;
+/// Part of the smaller side of an image that a fitted radius covers. The
+/// images have some empty room around the creature.
+const FITTED_RADIUS: f32 = 0.4;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<CreatureBody>();
+    app.add_systems(
+        Update,
+        push_apart_creatures
+            .after(move_creatures)
+            .before(apply_movement)
+            .in_set(AppSet::Update),
+    );
+}
+
+/// The circle a creature takes up when creatures collide.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct CreatureBody {
+    pub mass: f32,
+    /// In world units.
+    pub radius: f32,
+}
+
+impl CreatureBody {
+    /// The radius of a creature with `image` at a scale of 1, if its level
+    /// doesn't set one.
+    pub fn fitted_radius(image: CreatureImage) -> f32 {
+        image.size().min_element() as f32 * image.default_scale() * FITTED_RADIUS
+    }
+}
+
+/// The creatures that take part in collisions. Dying creatures don't.
+type Bodies<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Transform,
+        &'static CreatureBody,
+        Has<MovementFrozen>,
+    ),
+    (With<Creature>, Without<DeathAnimation>),
+>;
+
+fn push_apart_creatures(
+    active_level: Option<Res<ActiveLevel>>,
+    levels: Res<Assets<LevelDefinition>>,
+    grid: Res<SpatialGrid>,
+    bodies: Bodies,
+    mut controllers: Query<(Entity, &mut MovementController), With<CreatureBody>>,
+) {
+    let enabled = active_level
+        .and_then(|active_level| levels.get(&active_level.0))
+        .is_some_and(|level| level.collisions);
+    let max_radius = bodies
+        .iter()
+        .map(|(_, body, _)| body.radius)
+        .fold(0.0, f32::max);
+
+    for (entity, mut controller) in &mut controllers {
+        // Frozen creatures don't move, but still push others.
+        let push = match bodies.get(entity) {
+            Ok((transform, body, false)) if enabled => {
+                separation(entity, transform, body, max_radius, &grid, &bodies)
+            }
+            _ => Vec2::ZERO,
+        };
+        if controller.push != push {
+            controller.push = push;
+        }
+    }
+}
+
+/// The push away from all creatures that overlap `entity`.
+fn separation(
+    entity: Entity,
+    transform: &Transform,
+    body: &CreatureBody,
+    max_radius: f32,
+    grid: &SpatialGrid,
+    bodies: &Bodies,
+) -> Vec2 {
+    let position = transform.translation.xy();
+    let mut push = Vec2::ZERO;
+    // The grid is from the end of the last frame, which is where the
+    // creatures still are.
+    for other in grid.query_circle(position, body.radius + max_radius) {
+        if other == entity {
+            continue;
+        }
+        let Ok((other_transform, other_body, other_frozen)) = bodies.get(other) else {
+            continue;
+        };
+        let offset = position - other_transform.translation.xy();
+        let overlap = body.radius + other_body.radius - offset.length();
+        if overlap <= 0.0 {
+            continue;
+        }
+        // Creatures on the same spot go in different directions.
+        let direction = offset
+            .try_normalize()
+            .unwrap_or_else(|| Vec2::from_angle(entity.index() as f32));
+        let total_mass = body.mass + other_body.mass;
+        let share = if other_frozen {
+            1.0
+        } else if total_mass > 0.0 {
+            other_body.mass / total_mass
+        } else {
+            0.5
+        };
+        push += direction * overlap * share * STIFFNESS;
+    }
+    push
+}
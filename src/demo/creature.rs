@@ -5,40 +5,73 @@
 use std::time::Duration;
 
 use bevy::{
-    audio::Volume,
-    ecs::{system::RunSystemOnce as _, world::Command},
+    ecs::{
+        entity::{Entities, EntityHashSet},
+        system::RunSystemOnce as _,
+        world::{Command, EntityWorldMut},
+    },
     prelude::*,
     render::texture::{ImageLoaderSettings, ImageSampler},
+    utils::HashMap,
     window::PrimaryWindow,
 };
 use rand::{distributions::Uniform, prelude::Distribution, Rng};
 
 use crate::{
     asset_tracking::LoadResource,
-    audio::SoundEffect,
+    audio::PlaySoundEffect,
     demo::{
         animation::CreatureAnimation,
-        movement::{MovementController, ScreenBounce},
+        collision::CreatureBody,
+        difficulty::Difficulty,
+        events::{BulletLanded, CreatureHit, CreatureKilled, GameOver, GameOverReason, ShotFired},
+        movement::{MovementController, MovementFrozen, ScreenBounce, UnscaledTime},
         movement_pattern::MovementPattern,
+        powerup::{ActivePowerUps, PowerUpKind, SpawnPowerUp, MULTI_BALL_COUNT},
+        scoring::{kill_points, PlayerScores},
+        spatial::SpatialGrid,
     },
-    screens::{GameplayArea, Screen},
+    playfield::window_to_world,
+    pool::{Pool, PoolApp, Release},
+    screens::{is_long_press, GameScore, GameplayArea, Screen},
     AppSet,
 };
 
 use super::{
-    creature_image::CreatureImage, custom_cursor::HideGameCursor, dust::DustAnimation,
-    movement::ScreenWrap, movement_pattern::MovementPatternDefinition,
+    creature_image::CreatureImage,
+    custom_cursor::HideGameCursor,
+    movement::ScreenWrap,
+    movement_pattern::MovementPatternDefinition,
+    particles::{EmitParticles, ParticleEmitter},
+    players::PlayerId,
 };
 
 const BULLET_DURATION_SEC: f32 = 0.3;
+/// Horizontal distance between balls thrown at once.
+const MULTI_BALL_SPREAD: f32 = 80.0;
 
 const fn default_shrink_duration() -> u64 {
     10_000
 }
 
+const fn default_scale() -> f32 {
+    1.0
+}
+
+const fn default_mass() -> f32 {
+    1.0
+}
+
+const fn default_split_scale() -> f32 {
+    0.6
+}
+
 pub(super) fn plugin(app: &mut App) {
-    app.register_type::<Creature>();
+    app.register_type::<(Creature, CreatureScale, OnDeath)>();
     app.load_resource::<CreatureAssets>();
+    app.add_event::<ThrowBall>();
+    app.init_pool::<Bullet>(reset_bullet);
+    app.init_pool::<Creature>(reset_creature);
 
     // Record directional input as movement controls.
     app.add_systems(
@@ -47,12 +80,13 @@ pub(super) fn plugin(app: &mut App) {
             tick_bullets.in_set(AppSet::TickTimers),
             tick_death_animation.in_set(AppSet::TickTimers),
             record_player_click_input
-                .run_if(resource_exists::<CreatureAssets>)
                 .run_if(in_state(Screen::Gameplay))
                 .in_set(AppSet::RecordInput),
             (
+                throw_balls.run_if(in_state(Screen::Gameplay)),
                 update_bullet_animation,
                 process_bullets_landing,
+                (kill_hit_creatures, play_landing_sounds),
                 process_bullets_falling.run_if(in_state(Screen::Gameplay)),
                 end_game_on_too_many_creatures,
             )
@@ -62,11 +96,10 @@ pub(super) fn plugin(app: &mut App) {
             reaper,
         ),
     );
-
-    DustAnimation::init(app);
 }
 
-fn tick_bullets(time: Res<Time>, mut query: Query<&mut Bullet>) {
+/// Bullets run on real time so they feel the same during slow motion.
+fn tick_bullets(time: Res<Time<Real>>, mut query: Query<&mut Bullet>) {
     for mut bullet in &mut query.iter_mut() {
         bullet.timer.tick(time.delta());
     }
@@ -102,12 +135,12 @@ fn process_bullets_falling(
     mut bullets: Query<(Entity, &Transform, &mut MovementController), With<FallingBulletMarker>>,
     mut commands: Commands,
     gameplay_area: Res<GameplayArea>,
-    time: Res<Time>,
+    time: Res<Time<Real>>,
 ) {
     // remove bullets when they are out of the screen
     for (entity, transform, mut movement) in &mut bullets {
         if transform.translation.y < gameplay_area.main_area.min.y {
-            commands.entity(entity).despawn();
+            commands.add(Release::<Bullet>::new(entity));
         } else {
             let acceleration = 0.02;
             movement.intent.y -= time.delta().as_millis() as f32 * acceleration;
@@ -115,75 +148,114 @@ fn process_bullets_falling(
     }
 }
 
-fn process_bullets_landing(
-    creatures: Query<
-        (Entity, &Transform, &CreatureImage),
-        (With<Creature>, Without<DeathAnimation>),
-    >,
-    mut bullets: Query<(Entity, &Bullet, &Transform, &mut MovementController)>,
+pub(super) fn process_bullets_landing(
+    creatures: Query<&CreatureImage, (With<Creature>, Without<DeathAnimation>)>,
+    grid: Res<SpatialGrid>,
+    mut bullets: Query<(
+        Entity,
+        &Bullet,
+        &PlayerId,
+        &Transform,
+        &mut MovementController,
+    )>,
     mut commands: Commands,
-    creature_assets: Res<CreatureAssets>,
+    mut landed_events: EventWriter<BulletLanded>,
+    mut hit_events: EventWriter<CreatureHit>,
 ) {
-    let mut hits = Vec::new();
+    let mut landed = Vec::new();
     let rng = &mut rand::thread_rng();
-    for (entity, bullet, transform, mut movement) in &mut bullets {
+    for (entity, bullet, player, transform, mut movement) in &mut bullets {
         if !bullet.timer.just_finished() {
             continue;
         }
-        DustAnimation::spawn(&mut commands, &creature_assets, transform.translation.xy());
-        hits.push((entity, transform.translation.xy()));
+        commands.add(EmitParticles {
+            effect: "dust".to_string(),
+            position: transform.translation.xy(),
+        });
+        landed.push((*player, transform.translation.xy()));
         commands.entity(entity).insert(FallingBulletMarker);
         // bounce up
         movement.intent.y = 3.0;
         // and randomly to the side
         movement.intent.x = rng.sample(Uniform::new(-1.0, 1.0));
     }
-    if hits.is_empty() {
+    if landed.is_empty() {
         return;
     }
 
     // Bullet has landed.
 
-    let mut found_target = false;
-    for (entity, transform, image) in &creatures {
-        let scaled_image_dimension = image.size().as_vec2() * transform.scale.truncate();
-        let bounding_box =
-            Rect::from_center_size(transform.translation.truncate(), scaled_image_dimension);
-        if hits
-            .iter()
-            .any(|(_, click_pos)| bounding_box.contains(*click_pos))
-        {
-            commands.add(KillCreature(entity));
-            found_target = true;
+    // A creature only goes to the player whose ball landed on it first.
+    let mut hit_creatures = EntityHashSet::default();
+    for (player, position) in landed {
+        let mut hits = 0;
+        for entity in grid.query_point(position) {
+            let Ok(image) = creatures.get(entity) else {
+                continue;
+            };
+            if hit_creatures.insert(entity) {
+                hit_events.send(CreatureHit {
+                    player,
+                    entity,
+                    image: *image,
+                });
+                hits += 1;
+            }
         }
+        landed_events.send(BulletLanded {
+            player,
+            position,
+            hits,
+        });
     }
+}
+
+pub(super) fn kill_hit_creatures(mut events: EventReader<CreatureHit>, mut commands: Commands) {
+    for event in events.read() {
+        commands.add(KillCreature {
+            entity: event.entity,
+            player: Some(event.player),
+        });
+    }
+}
 
+/// One sound for all bullets that landed at once: a hit if any of them hit.
+fn play_landing_sounds(
+    mut events: EventReader<BulletLanded>,
+    mut commands: Commands,
+    creature_assets: Res<CreatureAssets>,
+) {
+    let mut landed = events.read().peekable();
+    if landed.peek().is_none() {
+        return;
+    }
+    let found_target = landed.any(|event| !event.missed());
     if found_target {
-        commands.spawn((
-            AudioBundle {
-                source: creature_assets.hit.clone(),
-                settings: PlaybackSettings::DESPAWN.with_volume(Volume::new(0.5)),
-            },
-            SoundEffect,
-        ));
+        commands.add(PlaySoundEffect {
+            source: creature_assets.hit.clone(),
+            volume: 0.5,
+        });
     } else {
-        commands.spawn((
-            AudioBundle {
-                source: creature_assets.miss.clone(),
-                settings: PlaybackSettings::DESPAWN,
-            },
-            SoundEffect,
-        ));
+        commands.add(PlaySoundEffect::new(creature_assets.miss.clone()));
     }
 }
 
+/// The area around a creature in which a landing bullet hits it.
+pub fn hitbox(image: &CreatureImage, transform: &Transform, difficulty: &Difficulty) -> Rect {
+    let scaled_image_dimension =
+        image.size().as_vec2() * transform.scale.truncate() * difficulty.modifiers().hitbox;
+    Rect::from_center_size(transform.translation.truncate(), scaled_image_dimension)
+}
+
 fn end_game_on_too_many_creatures(
     creatures: Query<Entity, With<Creature>>,
-    mut next_screen: ResMut<NextState<Screen>>,
+    mut game_over_events: EventWriter<GameOver>,
 ) {
     let count = creatures.iter().count();
     if count > 100 {
-        next_screen.set(Screen::Score);
+        game_over_events.send(GameOver {
+            reason: GameOverReason::Overrun,
+        });
     }
 }
 
@@ -203,10 +275,17 @@ pub struct SpawnCreature {
     /// true: wraps on the screen edge
     /// false (default): bounces on the screen edge
     pub wrap: bool,
+    /// Multiplier on top of [`CreatureImage::default_scale`].
+    pub scale: f32,
+    /// See [`CreatureDefinition::mass`].
+    pub mass: f32,
+    /// See [`CreatureDefinition::radius`].
+    pub radius: Option<f32>,
+    pub on_death: Vec<DeathEffect>,
 }
 
 /// A command to spawn the player character.
-#[derive(Debug, Clone, Reflect, serde::Deserialize)]
+#[derive(Debug, Clone, Reflect, serde::Serialize, serde::Deserialize)]
 pub struct CreatureDefinition {
     #[serde(default = "CreatureImage::fox")]
     pub image: CreatureImage,
@@ -218,6 +297,45 @@ pub struct CreatureDefinition {
     pub shrink_duration_ms: u64,
     #[serde(default)]
     pub wrap: bool,
+    /// Multiplier on top of the default size of the image.
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    /// How hard the creature is to push around when creatures collide, see
+    /// [`LevelDefinition::collisions`](super::level::LevelDefinition::collisions).
+    #[serde(default = "default_mass")]
+    pub mass: f32,
+    /// The size of the creature when creatures collide, at a `scale` of 1.
+    /// None fits it to the image.
+    #[serde(default)]
+    pub radius: Option<f32>,
+    /// Effects applied when the creature is killed, in order.
+    #[serde(default)]
+    pub on_death: Vec<DeathEffect>,
+}
+
+/// Something that happens when a creature is killed.
+#[derive(Debug, Clone, Reflect, serde::Serialize, serde::Deserialize)]
+pub enum DeathEffect {
+    /// Split into `count` smaller copies of the creature that move according
+    /// to `movement`. The copies don't inherit the death effects.
+    Split {
+        count: u32,
+        movement: MovementPatternDefinition,
+        /// None keeps the speed of the killed creature.
+        #[serde(default)]
+        max_speed: Option<f32>,
+        /// Relative to the scale of the killed creature.
+        #[serde(default = "default_split_scale")]
+        scale: f32,
+    },
+    /// Kill all other creatures within `radius` pixels.
+    Explode { radius: f32 },
+    /// Drop a power-up where the creature died.
+    DropPowerUp {
+        kind: PowerUpKind,
+        max_speed: f32,
+        movement: MovementPatternDefinition,
+    },
 }
 
 impl Command for SpawnCreature {
@@ -229,6 +347,8 @@ impl Command for SpawnCreature {
 fn spawn_creature(
     In(config): In<SpawnCreature>,
     mut commands: Commands,
+    mut pool: ResMut<Pool<Creature>>,
+    entities: &Entities,
     creature_assets: Res<CreatureAssets>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
 ) {
@@ -247,17 +367,11 @@ fn spawn_creature(
     let texture_atlas_layout = texture_atlas_layouts.add(layout);
     let creature_animation = CreatureAnimation::new(config.shrink_duration);
 
-    let texture = match config.image {
-        CreatureImage::Fox => creature_assets.fox.clone(),
-        CreatureImage::Snake => creature_assets.snake.clone(),
-        CreatureImage::Mouse => creature_assets.mouse.clone(),
-        CreatureImage::Hedgehog => creature_assets.hedgehog.clone(),
-        CreatureImage::Weasel => creature_assets.weasel.clone(),
-        CreatureImage::Duck => creature_assets.ducky.clone(),
-    };
+    let texture = creature_assets.image(config.image);
 
-    let scale = config.image.default_scale();
-    let mut entity = commands.spawn((
+    let scale = config.image.default_scale() * config.scale;
+    let mut entity = pool.spawn(&mut commands, entities);
+    entity.insert((
         Name::new("Creature"),
         Creature,
         SpriteBundle {
@@ -278,68 +392,194 @@ fn spawn_creature(
         creature_animation,
         StateScoped(Screen::Gameplay),
         config.image,
+        CreatureScale(scale),
+        CreatureBody {
+            mass: config.mass,
+            radius: config
+                .radius
+                .unwrap_or_else(|| CreatureBody::fitted_radius(config.image))
+                * config.scale,
+        },
     ));
     if config.wrap {
         entity.insert(ScreenWrap);
     } else {
         entity.insert(ScreenBounce);
     }
+    if !config.on_death.is_empty() {
+        entity.insert(OnDeath(config.on_death));
+    }
 }
 
+/// Remove everything that makes a released creature move, animate or die.
+/// Spawning it again inserts these back.
+pub(super) fn reset_creature(entity: &mut EntityWorldMut) {
+    entity.remove::<(
+        MovementController,
+        MovementPattern,
+        CreatureAnimation,
+        DeathAnimation,
+        MovementFrozen,
+        ScreenWrap,
+        ScreenBounce,
+        OnDeath,
+    )>();
+}
+
+/// The scale of the creature sprite, including its
+/// [`CreatureImage::default_scale`].
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct CreatureScale(pub f32);
+
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct OnDeath(pub Vec<DeathEffect>);
+
+/// A player wants to throw a ball at `target`.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ThrowBall {
+    pub player: PlayerId,
+    pub target: Vec2,
+}
+
+/// A thrown ball. The player that threw it is in its [`PlayerId`].
 #[derive(Component, Clone, Reflect, Default)]
-struct Bullet {
+pub struct Bullet {
     pub timer: Timer,
+    /// Where the bullet lands. Bullets fly up and back down to the position
+    /// they were thrown at.
+    pub target: Vec2,
 }
 
 #[derive(Component, Clone, Reflect, Default)]
 struct FallingBulletMarker;
 
+/// Stop a released bullet from moving and leaving a trail in the pool.
+fn reset_bullet(entity: &mut EntityWorldMut) {
+    entity.remove::<(FallingBulletMarker, ParticleEmitter)>();
+    if let Some(mut movement) = entity.get_mut::<MovementController>() {
+        *movement = MovementController::default();
+    }
+}
+
+/// The mouse, or touch, is always used by the first player. Every touch
+/// throws when it ends, so several fingers can throw at once, unless it was a
+/// long press that paused the game.
 fn record_player_click_input(
     input: Res<ButtonInput<MouseButton>>,
     touches_input: Res<Touches>,
+    real_time: Res<Time<Real>>,
+    mut touch_starts: Local<HashMap<u64, Duration>>,
     window_query: Query<&Window, With<PrimaryWindow>>,
     camera_query: Query<(&Camera, &GlobalTransform)>,
-    creature_assets: Res<CreatureAssets>,
-    mut commands: Commands,
-    bullets: Query<&Bullet, Without<FallingBulletMarker>>,
+    mut throws: EventWriter<ThrowBall>,
 ) {
-    // There can be only one bullet at a time.
-    if !bullets.is_empty() {
+    let now = real_time.elapsed();
+    for touch in touches_input.iter_just_pressed() {
+        touch_starts.insert(touch.id(), now);
+    }
+    let mut touched = Vec::new();
+    for touch in touches_input.iter_just_released() {
+        // Touches that started while paused resume the game instead.
+        let Some(start) = touch_starts.remove(&touch.id()) else {
+            continue;
+        };
+        if !is_long_press(touch, now - start) {
+            touched.push(touch.position());
+        }
+    }
+    // Touches that ended while the game was paused don't get released here.
+    touch_starts.retain(|&id, _| touches_input.get_pressed(id).is_some());
+
+    // There is no window when running headless.
+    let Ok((camera, camera_global_transform)) = camera_query.get_single() else {
         return;
-    } else {
-        commands.insert_resource(HideGameCursor(false));
+    };
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+
+    let clicked = input
+        .just_pressed(MouseButton::Left)
+        .then(|| window.cursor_position())
+        .flatten();
+    for target in clicked
+        .into_iter()
+        .chain(touched)
+        .filter_map(|position| window_to_world(camera, camera_global_transform, position))
+    {
+        throws.send(ThrowBall {
+            player: PlayerId(0),
+            target,
+        });
     }
+}
 
-    let (camera, camera_global_transform) = camera_query.single();
-    let window = window_query.single();
+fn throw_balls(
+    mut throws: EventReader<ThrowBall>,
+    creature_assets: Res<CreatureAssets>,
+    mut commands: Commands,
+    mut pool: ResMut<Pool<Bullet>>,
+    entities: &Entities,
+    bullets: Query<&PlayerId, (With<Bullet>, Without<FallingBulletMarker>)>,
+    power_ups: Res<ActivePowerUps>,
+    difficulty: Res<Difficulty>,
+    mut hide_cursor: ResMut<HideGameCursor>,
+    mut shot_events: EventWriter<ShotFired>,
+) {
+    // The difficulty limits how many bullets each player can have in the air
+    // at a time.
+    let max_bullets = difficulty.modifiers().balls as usize;
+    let mut in_flight = HashMap::<PlayerId, usize>::new();
+    for player in &bullets {
+        *in_flight.entry(*player).or_default() += 1;
+    }
 
-    if input.just_pressed(MouseButton::Left) || touches_input.any_just_pressed() {
-        if let Some(p) = window
-            .cursor_position()
-            .or_else(|| touches_input.first_pressed_position())
-            .and_then(|cursor| camera.viewport_to_world_2d(camera_global_transform, cursor))
-        {
-            commands.spawn((
+    for &ThrowBall { player, target } in throws.read() {
+        let player_in_flight = in_flight.entry(player).or_default();
+        if *player_in_flight >= max_bullets {
+            continue;
+        }
+        let count = if power_ups.is_active(PowerUpKind::MultiBall) {
+            MULTI_BALL_COUNT
+        } else {
+            1
+        };
+        for i in 0..count {
+            // spread additional balls evenly to the sides of the cursor
+            let offset = (i as f32 - (count - 1) as f32 / 2.0) * MULTI_BALL_SPREAD;
+            let target = target + Vec2::X * offset;
+            shot_events.send(ShotFired { player, target });
+            pool.spawn(&mut commands, entities).insert((
                 Name::new("Bullet"),
                 Bullet {
                     timer: Timer::from_seconds(BULLET_DURATION_SEC, TimerMode::Once),
+                    target,
                 },
+                player,
                 SpriteBundle {
                     texture: creature_assets.ball.clone(),
-                    transform: Transform::from_translation(p.extend(2.0)),
+                    sprite: Sprite {
+                        color: player.color(),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(target.extend(2.0)),
                     ..Default::default()
                 },
                 MovementController::default(),
+                UnscaledTime,
+                ParticleEmitter::new("trail"),
             ));
-            commands.spawn((
-                AudioBundle {
-                    source: creature_assets.shot.clone(),
-                    settings: PlaybackSettings::DESPAWN,
-                },
-                SoundEffect,
-            ));
-            commands.insert_resource(HideGameCursor(true));
         }
+        *player_in_flight += count;
+        commands.add(PlaySoundEffect::new(creature_assets.shot.clone()));
+    }
+
+    // The cursor shows whether the mouse player can throw.
+    let hide = in_flight.get(&PlayerId(0)).copied().unwrap_or_default() >= max_bullets;
+    if hide_cursor.0 != hide {
+        hide_cursor.0 = hide;
     }
 }
 
@@ -394,6 +634,20 @@ impl CreatureAssets {
     pub const PATH_MISS: &'static str = "audio/sound_effects/miss.ogg";
 }
 
+impl CreatureAssets {
+    /// The texture for `image`.
+    pub fn image(&self, image: CreatureImage) -> Handle<Image> {
+        match image {
+            CreatureImage::Fox => self.fox.clone(),
+            CreatureImage::Snake => self.snake.clone(),
+            CreatureImage::Mouse => self.mouse.clone(),
+            CreatureImage::Hedgehog => self.hedgehog.clone(),
+            CreatureImage::Weasel => self.weasel.clone(),
+            CreatureImage::Duck => self.ducky.clone(),
+        }
+    }
+}
+
 impl FromWorld for CreatureAssets {
     fn from_world(world: &mut World) -> Self {
         let assets = world.resource::<AssetServer>();
@@ -456,24 +710,135 @@ impl FromWorld for CreatureAssets {
     }
 }
 
+/// A command to kill a creature, as if it was hit by a bullet.
 #[derive(Reflect, Clone)]
-struct KillCreature(Entity);
+pub struct KillCreature {
+    pub entity: Entity,
+    /// The player that gets the points for the kill.
+    pub player: Option<PlayerId>,
+}
 
 impl Command for KillCreature {
     fn apply(self, world: &mut World) {
-        if let Some(mut atlas) = world.get_mut::<TextureAtlas>(self.0) {
+        let Self { entity, player } = self;
+        if world.get_entity(entity).is_none() || world.get::<DeathAnimation>(entity).is_some() {
+            // Already dead, e.g. caught in an explosion and hit in the same frame.
+            return;
+        }
+        if let Some(mut atlas) = world.get_mut::<TextureAtlas>(entity) {
             // index 1 is for shot creatures
             atlas.index = 1;
         }
-        if let Some(mut movement) = world.get_mut::<MovementController>(self.0) {
+        if let Some(mut movement) = world.get_mut::<MovementController>(entity) {
             // freeze in place
             movement.intent_modifier = Vec2::ZERO;
         }
-        if let Some(mut transform) = world.get_mut::<Transform>(self.0) {
+        let mut position = Vec2::ZERO;
+        if let Some(mut transform) = world.get_mut::<Transform>(entity) {
             // put behind dust animation
             transform.translation.z = 0.4;
+            position = transform.translation.xy();
+        }
+        let combo = match player {
+            Some(player) => world
+                .resource::<PlayerScores>()
+                .get(player)
+                .map_or(0, |score| score.combo),
+            None => world.resource::<GameScore>().combo,
+        };
+        world.send_event(CreatureKilled {
+            position,
+            player,
+            points: kill_points(combo),
+        });
+        let on_death = world
+            .entity_mut(entity)
+            .insert(DeathAnimation::new())
+            .take::<OnDeath>();
+        if let Some(OnDeath(effects)) = on_death {
+            world.run_system_once_with((entity, player, effects), apply_death_effects);
+        }
+    }
+}
+
+fn apply_death_effects(
+    In((dead, player, effects)): In<(Entity, Option<PlayerId>, Vec<DeathEffect>)>,
+    mut commands: Commands,
+    dead_query: Query<(
+        &Transform,
+        &CreatureImage,
+        &CreatureScale,
+        &MovementController,
+        &CreatureAnimation,
+        &CreatureBody,
+        Has<ScreenWrap>,
+    )>,
+    alive_creatures: Query<&Transform, (With<Creature>, Without<DeathAnimation>)>,
+    grid: Res<SpatialGrid>,
+) {
+    let Ok((transform, image, scale, movement, animation, body, wrap)) = dead_query.get(dead)
+    else {
+        return;
+    };
+    let pos = transform.translation.xy();
+    for effect in effects {
+        match effect {
+            DeathEffect::Split {
+                count,
+                movement: pattern,
+                max_speed,
+                scale: split_scale,
+            } => {
+                // The copies are smaller and lighter.
+                let multiplier = scale.0 / image.default_scale();
+                for i in 0..count {
+                    // spread the children around the dead creature so they
+                    // don't start out as a single pile
+                    let angle = i as f32 * std::f32::consts::TAU / count as f32;
+                    commands.add(SpawnCreature {
+                        image: *image,
+                        max_speed: max_speed.unwrap_or(movement.max_speed),
+                        pos: pos + Vec2::from_angle(angle) * 20.0,
+                        movement: pattern.build(),
+                        shrink_duration: animation.duration(),
+                        wrap,
+                        scale: multiplier * split_scale,
+                        mass: body.mass * split_scale,
+                        radius: Some(body.radius / multiplier),
+                        on_death: Vec::new(),
+                    });
+                }
+            }
+            DeathEffect::Explode { radius } => {
+                commands.add(EmitParticles {
+                    effect: "dust".to_string(),
+                    position: pos,
+                });
+                // The grid has the hitboxes, so check the distance to the
+                // center of the creatures, too.
+                for entity in grid.query_circle(pos, radius) {
+                    let Ok(other) = alive_creatures.get(entity) else {
+                        continue;
+                    };
+                    if entity != dead && other.translation.xy().distance(pos) <= radius {
+                        commands.add(KillCreature { entity, player });
+                    }
+                }
+            }
+            DeathEffect::DropPowerUp {
+                kind,
+                max_speed,
+                movement,
+            } => {
+                commands.add(SpawnPowerUp {
+                    kind,
+                    max_speed,
+                    pos,
+                    movement,
+                    delay: Duration::ZERO,
+                });
+            }
         }
-        world.entity_mut(self.0).insert(DeathAnimation::new());
     }
 }
 
@@ -486,12 +851,12 @@ pub struct DeathAnimation {
 
 fn reaper(
     mut commands: Commands,
-    mut query: Query<(&DeathAnimation, &mut Transform, &CreatureImage, Entity)>,
+    mut query: Query<(&DeathAnimation, &mut Transform, &CreatureScale, Entity)>,
     time: Res<Time>,
 ) {
-    for (animation, mut transform, image, entity) in &mut query {
+    for (animation, mut transform, scale, entity) in &mut query {
         if animation.timer.finished() {
-            commands.entity(entity).despawn();
+            commands.add(Release::<Creature>::new(entity));
         }
 
         // quadratic animation: start animation fast and slot it down
@@ -505,7 +870,7 @@ fn reaper(
         transform.rotate_local_y(std::f32::consts::TAU * y_rotations);
 
         // size reduction at quadratic speed from 1.0 to 0.25
-        transform.scale = Vec3::splat(image.default_scale() * (0.25 + explosive_entry * 0.75));
+        transform.scale = Vec3::splat(scale.0 * (0.25 + explosive_entry * 0.75));
     }
 }
 
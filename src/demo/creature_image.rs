@@ -2,7 +2,19 @@ use bevy::math::UVec2;
 use bevy::prelude::Component;
 use bevy::reflect::Reflect;
 
-#[derive(Clone, Copy, Debug, Reflect, serde::Deserialize, Component)]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Reflect,
+    serde::Serialize,
+    serde::Deserialize,
+    Component,
+)]
 pub(crate) enum CreatureImage {
     Fox,
     Snake,
@@ -15,6 +27,15 @@ pub(crate) enum CreatureImage {
 const DISPLAYED_SIZE: f32 = 128.0;
 
 impl CreatureImage {
+    pub const ALL: [CreatureImage; 6] = [
+        CreatureImage::Fox,
+        CreatureImage::Snake,
+        CreatureImage::Mouse,
+        CreatureImage::Weasel,
+        CreatureImage::Hedgehog,
+        CreatureImage::Duck,
+    ];
+
     pub fn fox() -> Self {
         Self::Fox
     }
@@ -1,12 +1,18 @@
 use bevy::prelude::*;
 
-use crate::screens::Screen;
+use crate::{playfield::window_to_ui, screens::Screen};
 
 pub fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Gameplay), setup);
     app.add_systems(OnExit(Screen::Gameplay), reset_cursor);
-    app.add_systems(Update, move_cursor.run_if(in_state(Screen::Gameplay)));
+    app.add_systems(
+        Update,
+        (detect_touch, move_cursor)
+            .chain()
+            .run_if(in_state(Screen::Gameplay)),
+    );
     app.init_resource::<HideGameCursor>();
+    app.init_resource::<UsingTouch>();
 }
 
 #[derive(Component)]
@@ -15,10 +21,16 @@ struct GameCursor;
 #[derive(Resource, Default)]
 pub struct HideGameCursor(pub bool);
 
+/// Whether the player last used a touch screen. There is nothing to point at
+/// with a finger, so the game cursor is hidden then.
+#[derive(Resource, Default)]
+struct UsingTouch(bool);
+
 // taken from https://github.com/bevyengine/bevy/discussions/8613
 fn setup(mut windows: Query<&mut Window>, mut commands: Commands, asset_server: Res<AssetServer>) {
-    let mut window: Mut<Window> = windows.single_mut();
-    window.cursor.visible = false;
+    if let Ok(mut window) = windows.get_single_mut() {
+        window.cursor.visible = false;
+    }
     let cursor_spawn: Vec3 = Vec3::ZERO;
 
     commands.spawn((
@@ -42,28 +54,50 @@ fn reset_cursor(
     mut commands: Commands,
     cursor_query: Query<(&GameCursor, Entity)>,
 ) {
-    let mut window: Mut<Window> = windows.single_mut();
-    window.cursor.visible = true;
+    if let Ok(mut window) = windows.get_single_mut() {
+        window.cursor.visible = true;
+    }
     for (_cursor, entity) in &cursor_query {
         commands.entity(entity).despawn();
     }
 }
 
+fn detect_touch(
+    touches: Res<Touches>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut using_touch: ResMut<UsingTouch>,
+) {
+    if touches.any_just_pressed() {
+        using_touch.0 = true;
+    } else if mouse.get_just_pressed().next().is_some() {
+        using_touch.0 = false;
+    }
+}
+
 // taken and modified from https://github.com/bevyengine/bevy/discussions/8613
 fn move_cursor(
     window: Query<&Window>,
+    camera: Query<&Camera>,
+    ui_scale: Res<UiScale>,
     mut cursor: Query<&mut Style, With<GameCursor>>,
     hide: Res<HideGameCursor>,
+    using_touch: Res<UsingTouch>,
 ) {
-    let window: &Window = window.single();
-    if let Some(position) = window.cursor_position() {
-        let mut img_style = cursor.single_mut();
+    let (Ok(window), Ok(camera)) = (window.get_single(), camera.get_single()) else {
+        return;
+    };
+    let Ok(mut img_style) = cursor.get_single_mut() else {
+        return;
+    };
+    let position = window.cursor_position();
+    if let Some(position) = position {
+        let position = window_to_ui(camera, &ui_scale, position);
         img_style.left = Val::Px(position.x - 24.0); // subtract half size of cursor image
         img_style.top = Val::Px(position.y - 24.0);
-        if hide.0 {
-            img_style.display = Display::None;
-        } else {
-            img_style.display = Display::DEFAULT;
-        }
+    }
+    if hide.0 || using_touch.0 || position.is_none() {
+        img_style.display = Display::None;
+    } else {
+        img_style.display = Display::DEFAULT;
     }
 }
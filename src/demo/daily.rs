@@ -0,0 +1,243 @@
+//! The daily challenge: a fixed number of generated waves that are the same
+//! for everyone on the same day.
+
+use std::time::Duration;
+
+use bevy::{prelude::*, utils::SystemTime};
+
+use crate::{
+    save::{DailyResult, SaveData},
+    screens::{GameScore, Screen},
+};
+
+use super::{
+    difficulty::Difficulty,
+    level::{wave_duration, GameMode},
+};
+
+/// Number of waves in a daily challenge.
+pub const DAILY_WAVES: u32 = 10;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Days since the unix epoch on the day Ludum Dare 56 started. Used to number
+/// the challenges.
+const FIRST_DAY: u32 = 20_000;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnExit(Screen::Gameplay), record_daily_result);
+}
+
+/// Save the result when leaving the game, also when quitting early, so there
+/// is only one attempt per day.
+fn record_daily_result(
+    game_score: Res<GameScore>,
+    mode: Res<GameMode>,
+    difficulty: Res<Difficulty>,
+    mut save: ResMut<SaveData>,
+) {
+    if let GameMode::Daily { day } = *mode {
+        save.daily.insert(
+            day,
+            DailyResult {
+                wave_times_ms: game_score
+                    .wave_times
+                    .iter()
+                    .map(|time| time.as_millis() as u64)
+                    .collect(),
+                win: game_score.win,
+                difficulty: *difficulty,
+            },
+        );
+        save.save();
+    }
+}
+
+/// The current date in the local time zone, counted in days since the unix
+/// epoch.
+///
+/// Days change at local midnight, so that everyone plays the same waves on
+/// the same date, wherever they are.
+pub fn today() -> u32 {
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+    (now + utc_offset(now)).div_euclid(SECONDS_PER_DAY) as u32
+}
+
+/// How far the local time zone is ahead of UTC at `timestamp`, in seconds.
+#[cfg(unix)]
+fn utc_offset(timestamp: i64) -> i64 {
+    let time = timestamp as libc::time_t;
+    // SAFETY: `tm` is plain data, and the pointers are valid during the call.
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        return 0;
+    }
+    tm.tm_gmtoff as i64
+}
+
+/// How far the local time zone is ahead of UTC at `timestamp`, in seconds.
+#[cfg(windows)]
+fn utc_offset(timestamp: i64) -> i64 {
+    let time = timestamp as libc::time_t;
+    // SAFETY: `tm` is plain data, and the pointers are valid during the calls.
+    let mut local: libc::tm = unsafe { std::mem::zeroed() };
+    let mut utc: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_s(&mut local, &time) != 0 || libc::gmtime_s(&mut utc, &time) != 0 }
+    {
+        return 0;
+    }
+    // Windows has no `tm_gmtoff`. The local date is at most a day away from
+    // the UTC one, which may be in another year.
+    let days = match local.tm_year.cmp(&utc.tm_year) {
+        std::cmp::Ordering::Less => -1,
+        std::cmp::Ordering::Equal => local.tm_yday - utc.tm_yday,
+        std::cmp::Ordering::Greater => 1,
+    };
+    let seconds = |tm: &libc::tm| tm.tm_hour * 60 * 60 + tm.tm_min * 60 + tm.tm_sec;
+    days as i64 * SECONDS_PER_DAY + (seconds(&local) - seconds(&utc)) as i64
+}
+
+/// How far the local time zone is ahead of UTC, in seconds.
+#[cfg(target_family = "wasm")]
+fn utc_offset(_timestamp: i64) -> i64 {
+    // The browser gives the minutes from local time to UTC.
+    -(js_sys::Date::new_0().get_timezone_offset() as i64) * 60
+}
+
+/// The seed used to generate the waves of the challenge on `day`.
+pub fn seed(day: u32) -> u64 {
+    (day as u64).wrapping_mul(0x2545_F491_4F6C_DD1D)
+}
+
+/// The number shown to players for the challenge on `day`.
+pub fn challenge_number(day: u32) -> u32 {
+    day.saturating_sub(FIRST_DAY)
+}
+
+/// A text summary of the result that can be shared with others, with one
+/// square per wave:
+/// - green, yellow or orange for waves cleared in the first, second or last
+///   third of the time
+/// - red for the wave that was lost
+/// - black for waves that were not reached
+pub fn share_text(day: u32, result: &DailyResult) -> String {
+    let cleared = result.wave_times_ms.len() as u32;
+    let duration = wave_duration(&result.difficulty);
+    let mut text = format!(
+        "Animal Arcade Daily #{} ({}) - {cleared}/{DAILY_WAVES}\n",
+        challenge_number(day),
+        result.difficulty.name(),
+    );
+    for wave in 0..DAILY_WAVES {
+        let square = match result.wave_times_ms.get(wave as usize) {
+            Some(&ms) => {
+                let fraction = Duration::from_millis(ms).as_secs_f32() / duration.as_secs_f32();
+                if fraction < 1.0 / 3.0 {
+                    '🟩'
+                } else if fraction < 2.0 / 3.0 {
+                    '🟨'
+                } else {
+                    '🟧'
+                }
+            }
+            None if wave == cleared && !result.win => '🟥',
+            None => '⬛',
+        };
+        text.push(square);
+        if wave % 5 == 4 {
+            text.push('\n');
+        }
+    }
+    text
+}
+
+/// The time per wave, readable with the fonts of the game.
+pub fn wave_times_text(result: &DailyResult) -> String {
+    let times = result
+        .wave_times_ms
+        .iter()
+        .enumerate()
+        .map(|(wave, ms)| format!("Wave {}: {:.1}s", wave + 1, *ms as f32 / 1000.0))
+        .collect::<Vec<_>>();
+    if times.is_empty() {
+        return "No wave cleared".to_string();
+    }
+    times
+        .chunks(2)
+        .map(|line| line.join("   "))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A result with waves cleared in the given fractions of the wave time.
+    fn result(fractions: &[f32], win: bool) -> DailyResult {
+        let duration = wave_duration(&Difficulty::Normal);
+        DailyResult {
+            wave_times_ms: fractions
+                .iter()
+                .map(|fraction| duration.mul_f32(*fraction).as_millis() as u64)
+                .collect(),
+            win,
+            difficulty: Difficulty::Normal,
+        }
+    }
+
+    #[test]
+    fn share_text_colors_waves_by_time() {
+        let text = share_text(FIRST_DAY + 5, &result(&[0.1, 0.5, 0.9], false));
+        assert_eq!(
+            text,
+            "Animal Arcade Daily #5 (Normal) - 3/10\n🟩🟨🟧🟥⬛\n⬛⬛⬛⬛⬛\n"
+        );
+    }
+
+    #[test]
+    fn share_text_has_no_lost_wave_when_won() {
+        let text = share_text(FIRST_DAY, &result(&[0.2; DAILY_WAVES as usize], true));
+        assert_eq!(
+            text,
+            "Animal Arcade Daily #0 (Normal) - 10/10\n🟩🟩🟩🟩🟩\n🟩🟩🟩🟩🟩\n"
+        );
+    }
+
+    #[test]
+    fn share_text_loses_the_first_wave() {
+        let text = share_text(FIRST_DAY, &result(&[], false));
+        assert!(text.ends_with("- 0/10\n🟥⬛⬛⬛⬛\n⬛⬛⬛⬛⬛\n"), "{text}");
+    }
+
+    #[test]
+    fn challenges_are_numbered_from_the_first_day() {
+        assert_eq!(challenge_number(FIRST_DAY), 0);
+        assert_eq!(challenge_number(FIRST_DAY + 42), 42);
+        assert_eq!(challenge_number(FIRST_DAY - 1), 0);
+    }
+
+    #[test]
+    fn every_day_has_its_own_seed() {
+        assert_eq!(seed(FIRST_DAY), seed(FIRST_DAY));
+        let seeds = (FIRST_DAY..FIRST_DAY + 1000)
+            .map(seed)
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(seeds.len(), 1000);
+    }
+
+    #[test]
+    fn today_is_close_to_the_utc_date() {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        // Time zones are between UTC-12 and UTC+14.
+        assert!((-12 * 60 * 60..=14 * 60 * 60).contains(&utc_offset(now)));
+        let utc_day = now.div_euclid(SECONDS_PER_DAY);
+        assert!((utc_day - 1..=utc_day + 1).contains(&(today() as i64)));
+    }
+}
@@ -0,0 +1,101 @@
+//! Difficulty settings that scale the level data when it is spawned.
+
+use bevy::prelude::*;
+
+use crate::save::SaveData;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Difficulty>();
+    app.init_resource::<Difficulty>();
+    app.add_systems(Startup, load_difficulty);
+}
+
+/// The difficulty the game is played at.
+#[derive(
+    Resource, Debug, Clone, Copy, PartialEq, Default, Reflect, serde::Serialize, serde::Deserialize,
+)]
+#[reflect(Resource)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    /// Modifiers set on the settings screen. High scores aren't recorded for
+    /// them, as they can be anything.
+    Custom(DifficultyModifiers),
+}
+
+/// Multipliers that are applied to the level data.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct DifficultyModifiers {
+    /// Multiplier for the speed of creatures.
+    pub speed: f32,
+    /// Multiplier for the time to clear a wave.
+    pub wave_duration: f32,
+    /// Multiplier for the size of creatures.
+    pub creature_scale: f32,
+    /// Multiplier for the area around creatures that counts as a hit.
+    pub hitbox: f32,
+    /// Number of balls that can be in the air at the same time.
+    pub balls: u32,
+}
+
+impl Default for DifficultyModifiers {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            wave_duration: 1.0,
+            creature_scale: 1.0,
+            hitbox: 1.0,
+            balls: 1,
+        }
+    }
+}
+
+impl Difficulty {
+    pub fn modifiers(&self) -> DifficultyModifiers {
+        match self {
+            Difficulty::Easy => DifficultyModifiers {
+                speed: 0.75,
+                wave_duration: 1.5,
+                creature_scale: 1.2,
+                hitbox: 1.25,
+                balls: 2,
+            },
+            Difficulty::Normal => DifficultyModifiers::default(),
+            Difficulty::Hard => DifficultyModifiers {
+                speed: 1.25,
+                wave_duration: 0.8,
+                creature_scale: 0.85,
+                hitbox: 0.9,
+                balls: 1,
+            },
+            Difficulty::Custom(modifiers) => *modifiers,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::Custom(_) => "Custom",
+        }
+    }
+
+    /// The next difficulty when cycling through them, with `custom` as the
+    /// modifiers of [`Difficulty::Custom`].
+    pub fn next(&self, custom: DifficultyModifiers) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Custom(custom),
+            Difficulty::Custom(_) => Difficulty::Easy,
+        }
+    }
+}
+
+fn load_difficulty(mut difficulty: ResMut<Difficulty>, save: Res<SaveData>) {
+    *difficulty = save.difficulty;
+}
@@ -0,0 +1,190 @@
+//! Procedurally generated waves for the endless mode.
+//!
+//! The waves get harder following a [`DifficultyCurve`] that is loaded from
+//! `assets/endless.curve.ron`.
+
+use bevy::prelude::*;
+use bevy_common_assets::ron::RonAssetPlugin;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::asset_tracking::LoadResource;
+
+use super::{
+    creature::CreatureDefinition, creature_image::CreatureImage, level::LevelDefinition,
+    movement_pattern::MovementPatternDefinition,
+};
+
+/// The images that are used for generated creatures.
+const IMAGES: [CreatureImage; 5] = [
+    CreatureImage::Fox,
+    CreatureImage::Snake,
+    CreatureImage::Mouse,
+    CreatureImage::Weasel,
+    CreatureImage::Hedgehog,
+];
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(RonAssetPlugin::<DifficultyCurve>::new(&["curve.ron"]));
+    app.load_resource::<EndlessAssets>();
+}
+
+/// A value that changes linearly with every wave until it reaches `limit`.
+#[derive(Debug, Clone, Copy, Reflect, serde::Deserialize)]
+pub struct Ramp {
+    pub start: f32,
+    pub per_wave: f32,
+    pub limit: f32,
+}
+
+impl Ramp {
+    pub fn at(&self, wave: u32) -> f32 {
+        let value = self.start + self.per_wave * wave as f32;
+        if self.per_wave >= 0.0 {
+            value.min(self.limit)
+        } else {
+            value.max(self.limit)
+        }
+    }
+}
+
+/// How the generated waves get harder over time.
+#[derive(Debug, Clone, Reflect, Asset, serde::Deserialize)]
+pub struct DifficultyCurve {
+    /// Number of creatures in a wave.
+    pub creatures: Ramp,
+    /// See [`CreatureDefinition::max_speed`].
+    pub max_speed: Ramp,
+    /// How much the speed of single creatures varies, as a fraction of
+    /// `max_speed`.
+    pub speed_variance: f32,
+    /// Duration of one cycle of `Periodic` and `Circle` movement.
+    pub pattern_duration_ms: Ramp,
+    /// Chance for a creature to move in a `Periodic` pattern.
+    pub periodic_chance: Ramp,
+    /// Chance for a creature to move in a `Circle` pattern.
+    pub circle_chance: Ramp,
+    /// Chance for a creature to wrap around the screen instead of bouncing.
+    pub wrap_chance: Ramp,
+}
+
+#[derive(Resource, Asset, Reflect, Clone)]
+pub struct EndlessAssets {
+    #[dependency]
+    pub curve: Handle<DifficultyCurve>,
+}
+
+impl FromWorld for EndlessAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            curve: assets.load("endless.curve.ron"),
+        }
+    }
+}
+
+/// Generate the level for a wave. The same seed and wave always result in the
+/// same level.
+pub fn generate_wave(curve: &DifficultyCurve, seed: u64, wave: u32) -> LevelDefinition {
+    let mut rng = StdRng::seed_from_u64(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ wave as u64);
+
+    let count = curve.creatures.at(wave).round().max(1.0) as usize;
+    let max_speed = curve.max_speed.at(wave);
+    let duration_ms = curve.pattern_duration_ms.at(wave).max(100.0) as u64;
+    let periodic_chance = curve.periodic_chance.at(wave);
+    let circle_chance = curve.circle_chance.at(wave);
+    let wrap_chance = curve.wrap_chance.at(wave).clamp(0.0, 1.0);
+
+    let creatures = (0..count)
+        .map(|_| {
+            let direction = Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU));
+            let pattern = rng.gen::<f32>();
+            let movement = if pattern < circle_chance {
+                MovementPatternDefinition::Circle {
+                    duration_ms,
+                    radius: rng.gen_range(0.2..0.6),
+                }
+            } else if pattern < circle_chance + periodic_chance {
+                MovementPatternDefinition::Periodic {
+                    duration_ms,
+                    max_speed: direction,
+                }
+            } else {
+                MovementPatternDefinition::Constant { speed: direction }
+            };
+            let variance = rng.gen_range(-curve.speed_variance..=curve.speed_variance);
+            CreatureDefinition {
+                image: IMAGES[rng.gen_range(0..IMAGES.len())],
+                max_speed: max_speed * (1.0 + variance),
+                pos: None,
+                movement,
+                shrink_duration_ms: 10_000,
+                wrap: rng.gen_bool(wrap_chance as f64),
+                scale: 1.0,
+                mass: 1.0,
+                radius: None,
+                on_death: Vec::new(),
+            }
+        })
+        .collect();
+
+    LevelDefinition {
+        creatures,
+        power_ups: Vec::new(),
+        seed: Some(rng.gen()),
+        collisions: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn curve() -> DifficultyCurve {
+        bevy::asset::ron::from_str(include_str!("../../assets/endless.curve.ron"))
+            .expect("the endless curve is valid")
+    }
+
+    fn wave_ron(seed: u64, wave: u32) -> String {
+        bevy::asset::ron::to_string(&generate_wave(&curve(), seed, wave))
+            .expect("levels can be serialized")
+    }
+
+    #[test]
+    fn same_seed_generates_the_same_waves() {
+        for wave in 0..20 {
+            assert_eq!(wave_ron(42, wave), wave_ron(42, wave));
+        }
+        assert_ne!(wave_ron(42, 3), wave_ron(43, 3));
+        assert_ne!(wave_ron(42, 3), wave_ron(42, 4));
+    }
+
+    #[test]
+    fn wave_size_follows_the_curve() {
+        let curve = curve();
+        assert_eq!(generate_wave(&curve, 7, 0).creatures.len(), 2);
+        assert_eq!(generate_wave(&curve, 7, 10).creatures.len(), 7);
+        assert_eq!(generate_wave(&curve, 7, 1000).creatures.len(), 25);
+    }
+
+    #[test]
+    fn ramp_stops_at_the_limit() {
+        let rising = Ramp {
+            start: 2.0,
+            per_wave: 0.5,
+            limit: 4.0,
+        };
+        assert_eq!(rising.at(0), 2.0);
+        assert_eq!(rising.at(3), 3.5);
+        assert_eq!(rising.at(4), 4.0);
+        assert_eq!(rising.at(100), 4.0);
+
+        let falling = Ramp {
+            start: 1000.0,
+            per_wave: -100.0,
+            limit: 500.0,
+        };
+        assert_eq!(falling.at(2), 800.0);
+        assert_eq!(falling.at(5), 500.0);
+        assert_eq!(falling.at(100), 500.0);
+    }
+}
@@ -0,0 +1,98 @@
+//! Events that happen during the game. Systems that react to the game, like
+//! sounds, scoring and the HUD, read these instead of looking into the game
+//! logic.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use super::{creature_image::CreatureImage, level::LevelDefinition, players::PlayerId};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<ShotFired>();
+    app.add_event::<BulletLanded>();
+    app.add_event::<CreatureHit>();
+    app.add_event::<CreatureKilled>();
+    app.add_event::<WaveStarted>();
+    app.add_event::<WaveCleared>();
+    app.add_event::<GameOver>();
+}
+
+/// A bullet was thrown at `target`.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ShotFired {
+    pub player: PlayerId,
+    pub target: Vec2,
+}
+
+/// A bullet landed at `position`. It missed if it didn't hit any creature.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct BulletLanded {
+    pub player: PlayerId,
+    pub position: Vec2,
+    /// Number of creatures that were hit.
+    pub hits: u32,
+}
+
+impl BulletLanded {
+    pub fn missed(&self) -> bool {
+        self.hits == 0
+    }
+}
+
+/// A landing bullet hit a creature. The creature is killed in response.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CreatureHit {
+    /// The player whose bullet hit the creature.
+    pub player: PlayerId,
+    pub entity: Entity,
+    pub image: CreatureImage,
+}
+
+/// A creature was killed, by a bullet or by the death effect of another
+/// creature.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CreatureKilled {
+    pub position: Vec2,
+    /// The player whose bullet killed the creature, directly or through a
+    /// death effect. `None` if no player did, e.g. from the dev console.
+    pub player: Option<PlayerId>,
+    /// The points awarded for the kill, with the combo of the player.
+    pub points: u32,
+}
+
+/// The creatures of a wave were spawned.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct WaveStarted {
+    /// The number of the wave, counted from 1.
+    pub wave: u32,
+}
+
+/// All creatures of a wave were killed. After the last wave, this comes right
+/// before the [`GameOver`] with [`GameOverReason::Won`].
+#[derive(Event, Debug, Clone)]
+pub struct WaveCleared {
+    /// The time it took to clear the wave.
+    pub time: Duration,
+    /// The time that was left on the wave timer.
+    pub time_left: Duration,
+    /// The level of the wave. The next wave is already spawned when this is
+    /// read.
+    pub level: Handle<LevelDefinition>,
+}
+
+/// The game is over and the score screen is shown next.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct GameOver {
+    pub reason: GameOverReason,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameOverReason {
+    /// The last wave was cleared.
+    Won,
+    /// The wave timer ran out before all creatures were killed.
+    TimeUp,
+    /// Too many creatures were alive at once.
+    Overrun,
+}
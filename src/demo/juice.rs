@@ -0,0 +1,233 @@
+//! Effects that make hits feel good: screen shake, hit-stop and floating
+//! score text. Particles are in [`particles`](super::particles).
+//!
+//! Every effect can be turned off in the [`EffectSettings`], for players that
+//! are bothered by motion or flashing.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{save::SaveData, screens::Screen, theme::palette::THEME_RED_DARK, AppSet};
+
+use super::{
+    events::{CreatureHit, CreatureKilled},
+    powerup::reset_power_ups,
+};
+
+/// Trauma added by every hit. Shaking grows with the square of the trauma.
+const HIT_TRAUMA: f32 = 0.35;
+/// Trauma lost per second.
+const TRAUMA_DECAY: f32 = 1.5;
+/// Camera offset at full trauma, in world units.
+const MAX_SHAKE_OFFSET: f32 = 12.0;
+/// Camera rotation at full trauma, in radians.
+const MAX_SHAKE_ANGLE: f32 = 0.03;
+/// How long time stands nearly still after a kill.
+const HIT_STOP: Duration = Duration::from_millis(60);
+/// Speed of the virtual time during a hit-stop.
+const HIT_STOP_SPEED: f32 = 0.05;
+/// How long floating score text is shown.
+const FLOATING_TEXT_DURATION: Duration = Duration::from_millis(800);
+/// How far floating score text rises, in world units.
+const FLOATING_TEXT_RISE: f32 = 40.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(Trauma, FloatingText)>();
+    app.init_resource::<Trauma>();
+    app.init_resource::<HitStop>();
+    // Power-ups reset the speed of time last, so a hit-stop that started
+    // during slow motion doesn't bring the slow motion back.
+    app.add_systems(
+        OnExit(Screen::Gameplay),
+        (reset_shake, end_hit_stop.before(reset_power_ups)),
+    );
+    app.add_systems(
+        Update,
+        (add_trauma, start_hit_stop, spawn_floating_text)
+            .after(AppSet::Update)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    // These run on real time, so they also end while time stands still.
+    app.add_systems(
+        Update,
+        (shake_camera, tick_hit_stop, update_floating_text).run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// Which effects are shown. Kept in the [`SaveData`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct EffectSettings {
+    pub screen_shake: bool,
+    pub hit_stop: bool,
+    pub particles: bool,
+    pub floating_text: bool,
+}
+
+impl Default for EffectSettings {
+    fn default() -> Self {
+        Self {
+            screen_shake: true,
+            hit_stop: true,
+            particles: true,
+            floating_text: true,
+        }
+    }
+}
+
+/// How much the camera shakes, between 0 and 1.
+#[derive(Resource, Debug, Clone, Copy, Default, Reflect)]
+#[reflect(Resource)]
+pub struct Trauma(pub f32);
+
+/// A running hit-stop, and the time speed to go back to afterwards.
+#[derive(Resource, Debug, Clone, Default)]
+struct HitStop(Option<(Timer, f32)>);
+
+/// Text that rises and fades out.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+struct FloatingText {
+    timer: Timer,
+    start: Vec2,
+}
+
+fn add_trauma(
+    mut events: EventReader<CreatureHit>,
+    save: Res<SaveData>,
+    mut trauma: ResMut<Trauma>,
+) {
+    let hits = events.read().count();
+    if hits > 0 && save.effects.screen_shake {
+        trauma.0 = (trauma.0 + HIT_TRAUMA * hits as f32).min(1.0);
+    }
+}
+
+fn shake_camera(
+    time: Res<Time<Real>>,
+    mut trauma: ResMut<Trauma>,
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+) {
+    if trauma.0 <= 0.0 && !trauma.is_changed() {
+        return;
+    }
+    trauma.0 = (trauma.0 - TRAUMA_DECAY * time.delta_seconds()).max(0.0);
+    let shake = trauma.0 * trauma.0;
+    let rng = &mut rand::thread_rng();
+    for mut transform in &mut camera_query {
+        let offset = Vec2::new(rng.gen_range(-1.0..=1.0), rng.gen_range(-1.0..=1.0))
+            * MAX_SHAKE_OFFSET
+            * shake;
+        transform.translation = offset.extend(transform.translation.z);
+        transform.rotation =
+            Quat::from_rotation_z(rng.gen_range(-1.0..=1.0) * MAX_SHAKE_ANGLE * shake);
+    }
+}
+
+fn reset_shake(
+    mut trauma: ResMut<Trauma>,
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+) {
+    trauma.0 = 0.0;
+    for mut transform in &mut camera_query {
+        transform.translation = Vec3::Z * transform.translation.z;
+        transform.rotation = Quat::IDENTITY;
+    }
+}
+
+fn start_hit_stop(
+    mut events: EventReader<CreatureKilled>,
+    save: Res<SaveData>,
+    mut hit_stop: ResMut<HitStop>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    if events.read().count() == 0 || !save.effects.hit_stop {
+        return;
+    }
+    // A kill during a hit-stop only restarts it.
+    let speed = match &hit_stop.0 {
+        Some((_, speed)) => *speed,
+        None => time.relative_speed(),
+    };
+    hit_stop.0 = Some((Timer::new(HIT_STOP, TimerMode::Once), speed));
+    time.set_relative_speed(HIT_STOP_SPEED);
+}
+
+fn tick_hit_stop(
+    real_time: Res<Time<Real>>,
+    mut hit_stop: ResMut<HitStop>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    let Some((timer, speed)) = &mut hit_stop.0 else {
+        return;
+    };
+    if time.relative_speed() != HIT_STOP_SPEED {
+        // Something else, like a power-up, changed the speed in the meantime.
+        *speed = time.relative_speed();
+        time.set_relative_speed(HIT_STOP_SPEED);
+    }
+    if timer.tick(real_time.delta()).finished() {
+        time.set_relative_speed(*speed);
+        hit_stop.0 = None;
+    }
+}
+
+fn end_hit_stop(mut hit_stop: ResMut<HitStop>, mut time: ResMut<Time<Virtual>>) {
+    if let Some((_, speed)) = hit_stop.0.take() {
+        time.set_relative_speed(speed);
+    }
+}
+
+fn spawn_floating_text(
+    mut commands: Commands,
+    mut events: EventReader<CreatureKilled>,
+    save: Res<SaveData>,
+) {
+    if !save.effects.floating_text {
+        events.clear();
+        return;
+    }
+    for event in events.read() {
+        commands.spawn((
+            Name::new("Floating Text"),
+            Text2dBundle {
+                text: Text::from_section(
+                    format!("+{}", event.points),
+                    TextStyle {
+                        font_size: 28.0,
+                        color: THEME_RED_DARK,
+                        ..default()
+                    },
+                ),
+                transform: Transform::from_translation(event.position.extend(5.0)),
+                ..default()
+            },
+            FloatingText {
+                timer: Timer::new(FLOATING_TEXT_DURATION, TimerMode::Once),
+                start: event.position,
+            },
+            StateScoped(Screen::Gameplay),
+        ));
+    }
+}
+
+fn update_floating_text(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut query: Query<(Entity, &mut FloatingText, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut floating, mut transform, mut text) in &mut query {
+        if floating.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let life = floating.timer.fraction();
+        let position = floating.start + Vec2::Y * FLOATING_TEXT_RISE * life;
+        transform.translation = position.extend(transform.translation.z);
+        for section in &mut text.sections {
+            section.style.color.set_alpha(1.0 - life);
+        }
+    }
+}
@@ -5,11 +5,22 @@ use bevy::ecs::world::Command;
 use bevy::prelude::*;
 use bevy_common_assets::ron::RonAssetPlugin;
 use rand::distributions::{Distribution, Uniform};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 
 use crate::asset_tracking::LoadResource;
-use crate::audio::SoundEffect;
+use crate::audio::PlaySoundEffect;
+use crate::config::AppConfig;
 use crate::demo::creature::CreatureDefinition;
 use crate::demo::creature::SpawnCreature;
+use crate::demo::daily::{self, DAILY_WAVES};
+use crate::demo::difficulty::Difficulty;
+use crate::demo::endless::{generate_wave, DifficultyCurve, EndlessAssets};
+use crate::demo::events::{GameOver, GameOverReason, WaveCleared, WaveStarted};
+use crate::demo::powerup::{PowerUp, PowerUpDefinition, PowerUpSpawner, SpawnPowerUp};
+use crate::demo::versus::Versus;
+use crate::pool::Release;
+use crate::save::SaveData;
 use crate::screens::GameScore;
 use crate::screens::GameplayArea;
 use crate::screens::Screen;
@@ -20,7 +31,12 @@ use std::time::Duration;
 use super::creature::Creature;
 use super::creature::DeathAnimation;
 
-const WAVE_DURATION: Duration = Duration::from_secs(20);
+pub const WAVE_DURATION: Duration = Duration::from_secs(20);
+
+/// The time to clear a wave at `difficulty`.
+pub fn wave_duration(difficulty: &Difficulty) -> Duration {
+    WAVE_DURATION.mul_f32(difficulty.modifiers().wave_duration)
+}
 
 #[derive(Resource, Debug, Clone, PartialEq, Reflect)]
 #[reflect(Resource)]
@@ -32,6 +48,25 @@ pub struct WaveCounter {
     pub wave: u32,
 }
 
+/// The wave the campaign starts at, counted from 1 like in the UI. Set with
+/// `--start-wave N` or on the level select screen.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct StartWave(pub u32);
+
+impl StartWave {
+    /// The wave from `--start-wave`, or the first one.
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self(config.start_wave.unwrap_or(1))
+    }
+}
+
+impl FromWorld for StartWave {
+    fn from_world(world: &mut World) -> Self {
+        Self::from_config(world.resource::<AppConfig>())
+    }
+}
+
 #[derive(Resource, Asset, Reflect, Clone)]
 pub struct WaveSound {
     #[dependency]
@@ -50,42 +85,98 @@ pub struct Levels {
     game_levels: Vec<Handle<LevelDefinition>>,
 }
 
+impl Levels {
+    /// The number of waves in the campaign.
+    pub fn count(&self) -> u32 {
+        self.game_levels.len() as u32
+    }
+}
+
 /// A definition of a single level, loaded from a RON file or directly defined in Rust
-#[derive(Debug, Clone, Reflect, Asset, serde::Deserialize)]
+#[derive(Debug, Clone, Default, Reflect, Asset, serde::Serialize, serde::Deserialize)]
 pub struct LevelDefinition {
-    creatures: Vec<CreatureDefinition>,
+    pub creatures: Vec<CreatureDefinition>,
+    #[serde(default)]
+    pub power_ups: Vec<PowerUpDefinition>,
+    /// Seed for placing creatures without a position. None places them
+    /// differently every time.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Creatures push each other apart instead of overlapping, see
+    /// [`collision`](super::collision).
+    #[serde(default)]
+    pub collisions: bool,
+}
+
+/// Which waves are played.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Reflect, Default)]
+#[reflect(Resource)]
+pub enum GameMode {
+    /// The fixed waves from [`Levels`].
+    #[default]
+    Campaign,
+    /// Endless procedurally generated waves. The same seed always generates
+    /// the same waves.
+    Endless { seed: u64 },
+    /// [`DAILY_WAVES`] generated waves that are the same for everyone on
+    /// `day`.
+    Daily { day: u32 },
+    /// Players take turns playing the same generated waves, see
+    /// [`Versus`](super::versus::Versus).
+    Versus { seed: u64 },
+    /// Endless generated waves, played by all players of the party at once.
+    /// No high scores are kept, since the score is shared.
+    Party { seed: u64 },
 }
 
 #[derive(Clone, Reflect, Resource, Default, PartialEq)]
 pub struct DevMode(pub bool);
 
+/// The level of the current wave.
+#[derive(Resource, Debug, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct ActiveLevel(pub Handle<LevelDefinition>);
+
+/// The levels of the campaign. To add more levels for the game, create a file
+/// in assets/levels/ that ends with .level.ron and add its path here.
+const CAMPAIGN: [&str; 18] = [
+    "levels/00_easy_start.level.ron",
+    "levels/01_more_easy_creatures.level.ron",
+    "levels/02_few_periodic.level.ron",
+    "levels/03_mixed_periodic.level.ron",
+    "levels/03.5_mouse.level.ron",
+    "levels/04_mixed_circles.level.ron",
+    "levels/05.level.ron",
+    "levels/06.level.ron",
+    "levels/06.5_fox.level.ron",
+    "levels/07.level.ron",
+    "levels/08_one_of_all.level.ron",
+    "levels/09_two_weasels.level.ron",
+    "levels/10_three_weasels.level.ron",
+    "levels/11_four_weasels.level.ron",
+    "levels/12.level.ron",
+    "levels/13.level.ron",
+    "levels/14.level.ron",
+    "levels/15.level.ron",
+];
+
 impl FromWorld for Levels {
     fn from_world(world: &mut World) -> Self {
+        let config = world.resource::<AppConfig>();
+        let paths = if let Some(level) = &config.level {
+            vec![level.clone()]
+        } else if let Some(campaign) = &config.campaign {
+            read_campaign(campaign).unwrap_or_else(|err| {
+                error!("Failed to read campaign {campaign:?}, using the default one: {err}");
+                CAMPAIGN.map(String::from).to_vec()
+            })
+        } else {
+            CAMPAIGN.map(String::from).to_vec()
+        };
+
         let assets = world.resource::<AssetServer>();
-        // To add more levels for the game, create a file in assets/levels/ that
-        // ends with .level.rin and add its path here.
-        let levels = [
-            "levels/00_easy_start.level.ron",
-            "levels/01_more_easy_creatures.level.ron",
-            "levels/02_few_periodic.level.ron",
-            "levels/03_mixed_periodic.level.ron",
-            "levels/03.5_mouse.level.ron",
-            "levels/04_mixed_circles.level.ron",
-            "levels/05.level.ron",
-            "levels/06.level.ron",
-            "levels/06.5_fox.level.ron",
-            "levels/07.level.ron",
-            "levels/08_one_of_all.level.ron",
-            "levels/09_two_weasels.level.ron",
-            "levels/10_three_weasels.level.ron",
-            "levels/11_four_weasels.level.ron",
-            "levels/12.level.ron",
-            "levels/13.level.ron",
-            "levels/14.level.ron",
-            "levels/15.level.ron",
-        ];
         Levels {
-            game_levels: levels
+            game_levels: paths
                 .into_iter()
                 .map(|path| assets.load::<LevelDefinition>(path))
                 .collect::<Vec<_>>(),
@@ -93,6 +184,22 @@ impl FromWorld for Levels {
     }
 }
 
+/// Read a campaign manifest: a RON list of level paths, relative to the assets
+/// folder.
+#[cfg(not(target_family = "wasm"))]
+fn read_campaign(path: &str) -> Result<Vec<String>, String> {
+    use bevy::asset::io::file::FileAssetReader;
+
+    let path = FileAssetReader::get_base_path().join("assets").join(path);
+    let content = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    bevy::asset::ron::from_str(&content).map_err(|err| err.to_string())
+}
+
+#[cfg(target_family = "wasm")]
+fn read_campaign(_path: &str) -> Result<Vec<String>, String> {
+    Err("campaign files are not supported on web".to_string())
+}
+
 impl FromWorld for WaveSound {
     fn from_world(world: &mut World) -> Self {
         let assets = world.resource::<AssetServer>();
@@ -114,7 +221,9 @@ pub(super) fn plugin(app: &mut App) {
     // Configure that ***.level.ron files loaded as assets map to a `LevelDefinition`.
     app.add_plugins(RonAssetPlugin::<LevelDefinition>::new(&["level.ron"]));
 
-    app.register_type::<WaveTimer>();
+    app.register_type::<(WaveTimer, GameMode, ActiveLevel, StartWave)>();
+    app.init_resource::<GameMode>();
+    app.init_resource::<StartWave>();
     app.load_resource::<WaveSound>();
     app.load_resource::<Levels>();
     app.init_resource::<DevMode>();
@@ -125,6 +234,9 @@ pub(super) fn plugin(app: &mut App) {
         Update,
         (
             tick_wave_timer.in_set(AppSet::TickTimers),
+            reload_active_level
+                .in_set(AppSet::Update)
+                .run_if(on_event::<AssetEvent<LevelDefinition>>()),
             (check_wave_spawn, check_wave_timer)
                 .chain()
                 .in_set(AppSet::Update)
@@ -133,16 +245,50 @@ pub(super) fn plugin(app: &mut App) {
             .run_if(resource_exists::<WaveSound>)
             .run_if(in_state(Screen::Gameplay)),
     );
+    // React in the same frame, so the game doesn't go on after it's over.
+    app.add_systems(
+        Update,
+        (
+            play_wave_sounds.run_if(resource_exists::<WaveSound>),
+            end_game,
+        )
+            .after(AppSet::Update),
+    );
 }
 
-fn add_resources(mut commands: Commands) {
-    commands.insert_resource(WaveTimer::default());
-    commands.insert_resource(WaveCounter::default());
+fn add_resources(
+    mut commands: Commands,
+    difficulty: Res<Difficulty>,
+    mode: Res<GameMode>,
+    start_wave: Res<StartWave>,
+    save: Res<SaveData>,
+    levels: Res<Levels>,
+    versus: Res<Versus>,
+) {
+    commands.insert_resource(GameScore::default());
+    commands.insert_resource(WaveTimer(Timer::new(
+        wave_duration(&difficulty),
+        TimerMode::Once,
+    )));
+
+    // Release builds only allow starting at waves that were reached before.
+    let start_wave = if cfg!(feature = "dev") {
+        start_wave.0
+    } else {
+        start_wave.0.min(save.unlocked_waves())
+    };
+    let wave = match *mode {
+        GameMode::Campaign => start_wave.clamp(1, levels.count().max(1)) - 1,
+        GameMode::Endless { .. } | GameMode::Party { .. } | GameMode::Daily { .. } => 0,
+        GameMode::Versus { .. } => versus.round - 1,
+    };
+    commands.insert_resource(WaveCounter { wave });
 }
 
 fn remove_resources(mut commands: Commands) {
     commands.remove_resource::<WaveTimer>();
     commands.remove_resource::<WaveCounter>();
+    commands.remove_resource::<ActiveLevel>();
 }
 
 fn tick_wave_timer(time: Res<Time>, mut timer: ResMut<WaveTimer>) {
@@ -151,71 +297,173 @@ fn tick_wave_timer(time: Res<Time>, mut timer: ResMut<WaveTimer>) {
 
 fn check_wave_spawn(
     mut timer: ResMut<WaveTimer>,
-    mut next_screen: ResMut<NextState<Screen>>,
     mut commands: Commands,
     mut wave_counter: ResMut<WaveCounter>,
-    mut game_score: ResMut<GameScore>,
     level_handles: Res<Levels>,
-    sound: Res<WaveSound>,
+    mode: Res<GameMode>,
+    endless_assets: Option<Res<EndlessAssets>>,
+    curves: Res<Assets<DifficultyCurve>>,
+    mut levels: ResMut<Assets<LevelDefinition>>,
     creatures: Query<Entity, With<Creature>>,
+    active_level: Option<Res<ActiveLevel>>,
+    mut started_events: EventWriter<WaveStarted>,
+    mut cleared_events: EventWriter<WaveCleared>,
+    mut game_over_events: EventWriter<GameOver>,
 ) {
     // If it's a first wave or the wave was cleared.
-    if wave_counter.wave == 0 || creatures.iter().len() == 0 {
-        // Last level done.
-        let Some(level_handle) = level_handles.game_levels.get(wave_counter.wave as usize) else {
-            commands.spawn((
-                AudioBundle {
-                    source: sound.win.clone(),
-                    settings: PlaybackSettings::DESPAWN,
-                },
-                SoundEffect,
-            ));
-            game_score.win = true;
-            next_screen.set(Screen::Score);
-            return;
+    // Creatures spawned by death effects exist before the killed creature is
+    // despawned, so they keep the wave going until they are dead as well.
+    if active_level.is_none() || creatures.iter().len() == 0 {
+        let next_level = match *mode {
+            GameMode::Campaign => level_handles
+                .game_levels
+                .get(wave_counter.wave as usize)
+                .cloned(),
+            GameMode::Endless { seed } | GameMode::Party { seed } => {
+                let Some(curve) = endless_assets.and_then(|assets| curves.get(&assets.curve))
+                else {
+                    // curve not loaded, yet
+                    return;
+                };
+                Some(levels.add(generate_wave(curve, seed, wave_counter.wave)))
+            }
+            GameMode::Daily { .. } if wave_counter.wave >= DAILY_WAVES => None,
+            GameMode::Daily { day } => {
+                let Some(curve) = endless_assets.and_then(|assets| curves.get(&assets.curve))
+                else {
+                    return;
+                };
+                Some(levels.add(generate_wave(curve, daily::seed(day), wave_counter.wave)))
+            }
+            // A turn is a single wave.
+            GameMode::Versus { .. } if active_level.is_some() => None,
+            GameMode::Versus { seed } => {
+                let Some(curve) = endless_assets.and_then(|assets| curves.get(&assets.curve))
+                else {
+                    return;
+                };
+                Some(levels.add(generate_wave(curve, seed, wave_counter.wave)))
+            }
         };
 
-        // Play sound on complete level.
-        if wave_counter.wave > 0 {
-            commands.spawn((
-                AudioBundle {
-                    source: sound.wave_cleared.clone(),
-                    settings: PlaybackSettings::DESPAWN,
-                },
-                SoundEffect,
-            ));
+        // The last wave is cleared, too, before the game is won.
+        if let Some(active_level) = &active_level {
+            cleared_events.send(WaveCleared {
+                time: timer.0.elapsed(),
+                time_left: timer.0.remaining(),
+                level: active_level.0.clone(),
+            });
         }
 
-        game_score.score = wave_counter.wave;
+        // Last level done.
+        let Some(level_handle) = next_level else {
+            game_over_events.send(GameOver {
+                reason: GameOverReason::Won,
+            });
+            return;
+        };
+
         wave_counter.wave += 1;
 
-        commands.add(SpawnLevel(level_handle.clone()));
+        commands.add(SpawnLevel(level_handle));
+        started_events.send(WaveStarted {
+            wave: wave_counter.wave,
+        });
         timer.0.reset();
     }
 }
 
+/// Respawn the current wave when its level file changes, so that levels can be
+/// tweaked while playing them.
+fn reload_active_level(
+    mut events: EventReader<AssetEvent<LevelDefinition>>,
+    mut commands: Commands,
+    active_level: Option<Res<ActiveLevel>>,
+    mut timer: ResMut<WaveTimer>,
+    wave_counter: Res<WaveCounter>,
+    creatures: Query<Entity, With<Creature>>,
+    power_ups: Query<Entity, Or<(With<PowerUp>, With<PowerUpSpawner>)>>,
+    mut started_events: EventWriter<WaveStarted>,
+) {
+    let Some(active_level) = active_level else {
+        return;
+    };
+    let modified = events.read().any(|event| match event {
+        AssetEvent::Modified { id } => *id == active_level.0.id(),
+        _ => false,
+    });
+    if !modified {
+        return;
+    }
+
+    info!("Reloading level {:?}", active_level.0.path());
+    for entity in &creatures {
+        commands.add(Release::<Creature>::new(entity));
+    }
+    for entity in &power_ups {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.add(SpawnLevel(active_level.0.clone()));
+    timer.0.reset();
+    started_events.send(WaveStarted {
+        wave: wave_counter.wave,
+    });
+}
+
 fn check_wave_timer(
-    mut next_screen: ResMut<NextState<Screen>>,
     timer: Res<WaveTimer>,
     alive_creatures: Query<&Creature, Without<DeathAnimation>>,
-    sound: Res<WaveSound>,
-    mut game_score: ResMut<GameScore>,
-    mut commands: Commands,
+    mut game_over_events: EventWriter<GameOver>,
 ) {
     // This means we've lost.
     if timer.0.just_finished() && !alive_creatures.is_empty() {
-        commands.spawn((
-            AudioBundle {
-                source: sound.lose.clone(),
-                settings: PlaybackSettings::DESPAWN,
-            },
-            SoundEffect,
-        ));
-        game_score.win = false;
-        next_screen.set(Screen::Score);
+        game_over_events.send(GameOver {
+            reason: GameOverReason::TimeUp,
+        });
     }
 }
 
+fn play_wave_sounds(
+    mut cleared_events: EventReader<WaveCleared>,
+    mut game_over_events: EventReader<GameOver>,
+    sound: Res<WaveSound>,
+    mut commands: Commands,
+) {
+    let cleared = cleared_events
+        .read()
+        .last()
+        .map(|_| sound.wave_cleared.clone());
+    let game_over = game_over_events
+        .read()
+        .last()
+        .map(|event| match event.reason {
+            GameOverReason::Won => sound.win.clone(),
+            GameOverReason::TimeUp | GameOverReason::Overrun => sound.lose.clone(),
+        });
+    // The last wave only plays the sound for the win.
+    let cleared = cleared.filter(|_| game_over.is_none());
+    for source in [cleared, game_over].into_iter().flatten() {
+        commands.add(PlaySoundEffect::new(source));
+    }
+}
+
+fn end_game(
+    mut events: EventReader<GameOver>,
+    mut game_score: ResMut<GameScore>,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mode: Res<GameMode>,
+) {
+    let Some(event) = events.read().last() else {
+        return;
+    };
+    if matches!(*mode, GameMode::Versus { .. }) {
+        // Versus turns end on their own.
+        return;
+    }
+    game_score.win = event.reason == GameOverReason::Won;
+    next_screen.set(Screen::Score);
+}
+
 pub(crate) struct SpawnLevel(pub Handle<LevelDefinition>);
 
 impl Command for SpawnLevel {
@@ -229,14 +477,26 @@ fn spawn_level(
     mut commands: Commands,
     gameplay_area: Res<GameplayArea>,
     levels: Res<Assets<LevelDefinition>>,
+    difficulty: Res<Difficulty>,
+    config: Res<AppConfig>,
+    wave_counter: Option<Res<WaveCounter>>,
 ) {
     let Some(level) = levels.get(&level_handle) else {
         // level not loaded, yet
         println!("Loading order wrong, level {level_handle:?} has not been loaded when it should have been spawned");
         return;
     };
+    commands.insert_resource(ActiveLevel(level_handle.clone()));
 
-    let mut rng = &mut rand::thread_rng();
+    // With --seed, levels without a seed get a different one for every wave.
+    let wave = wave_counter.map_or(0, |counter| counter.wave as u64);
+    let seed = level
+        .seed
+        .or_else(|| config.seed.map(|seed| seed.wrapping_add(wave)));
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
     let size = gameplay_area.main_area.size() - 256.0;
     let half_size = size / 2.0;
     let x_dist = Uniform::from(-half_size.x..half_size.x);
@@ -246,14 +506,29 @@ fn spawn_level(
         y: y_dist.sample(&mut rng),
     };
 
+    let modifiers = difficulty.modifiers();
     for creature in &level.creatures {
         commands.add(SpawnCreature {
             image: creature.image,
-            max_speed: creature.max_speed,
+            max_speed: creature.max_speed * modifiers.speed,
             pos: creature.pos.unwrap_or_else(&mut random_screen_pos),
             movement: creature.movement.build(),
             shrink_duration: Duration::from_millis(creature.shrink_duration_ms),
             wrap: creature.wrap,
+            scale: creature.scale * modifiers.creature_scale,
+            mass: creature.mass,
+            radius: creature.radius,
+            on_death: creature.on_death.clone(),
+        });
+    }
+
+    for power_up in &level.power_ups {
+        commands.add(SpawnPowerUp {
+            kind: power_up.kind,
+            max_speed: power_up.max_speed,
+            pos: power_up.pos.unwrap_or_else(&mut random_screen_pos),
+            movement: power_up.movement.clone(),
+            delay: Duration::from_millis(power_up.delay_ms),
         });
     }
 }
@@ -5,14 +5,28 @@
 
 use bevy::prelude::*;
 
+pub mod achievements;
 mod animation;
+pub mod bench;
+pub mod collision;
 pub mod creature;
 pub mod creature_image;
 mod custom_cursor;
-mod dust;
+pub mod daily;
+pub mod difficulty;
+pub mod endless;
+pub mod events;
+pub mod juice;
 pub mod level;
-mod movement;
+pub mod movement;
 pub mod movement_pattern;
+pub mod particles;
+pub mod players;
+pub mod powerup;
+pub mod scoring;
+pub mod spatial;
+pub mod stats;
+pub mod versus;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_plugins((
@@ -20,7 +34,24 @@ pub(super) fn plugin(app: &mut App) {
         movement::plugin,
         creature::plugin,
         level::plugin,
+        endless::plugin,
+        daily::plugin,
+        difficulty::plugin,
         movement_pattern::plugin,
         custom_cursor::plugin,
+        powerup::plugin,
+        players::plugin,
+        versus::plugin,
+        spatial::plugin,
+        collision::plugin,
+    ));
+    // Everything that reacts to gameplay events.
+    app.add_plugins((
+        events::plugin,
+        scoring::plugin,
+        achievements::plugin,
+        stats::plugin,
+        juice::plugin,
+        particles::plugin,
     ));
 }
@@ -13,14 +13,24 @@
 //! purposes. If you want to move the player in a smoother way,
 //! consider using a [fixed timestep](https://github.com/bevyengine/bevy/blob/main/examples/movement/physics_in_fixed_timestep.rs).
 
+use std::time::Duration;
+
 use bevy::prelude::*;
 
+use super::creature::CreatureScale;
 use super::creature_image::CreatureImage;
+use super::movement_pattern::MovementPattern;
 use crate::screens::{GameplayArea, Screen};
 use crate::AppSet;
 
 pub(super) fn plugin(app: &mut App) {
-    app.register_type::<(MovementController, ScreenWrap, ScreenBounce)>();
+    app.register_type::<(
+        MovementController,
+        ScreenWrap,
+        ScreenBounce,
+        MovementFrozen,
+        UnscaledTime,
+    )>();
 
     app.add_systems(
         Update,
@@ -37,7 +47,7 @@ pub(super) fn plugin(app: &mut App) {
 /// These are the movement parameters for our character controller.
 /// For now, this is only used for a single player, but it could power NPCs or
 /// other players as well.
-#[derive(Component, Reflect)]
+#[derive(Component, Reflect, Clone)]
 #[reflect(Component)]
 pub struct MovementController {
     /// The direction the character wants to move in.
@@ -50,6 +60,38 @@ pub struct MovementController {
     /// 1 world unit = 1 pixel when using the default 2D camera and no physics
     /// engine.
     pub max_speed: f32,
+
+    /// Velocity added on top of the intent, in world units per second. Used
+    /// to push colliding creatures apart.
+    pub push: Vec2,
+}
+
+impl MovementController {
+    /// The velocity in world units per second.
+    pub fn velocity(&self) -> Vec2 {
+        self.max_speed * self.intent * self.intent_modifier + self.push
+    }
+
+    /// Turn around when the entity at `position` sticks out of `area`.
+    pub fn bounce(&mut self, area: Rect, half_size: Vec2, position: Vec2) {
+        let min_y = area.min.y + half_size.y;
+        let max_y = area.max.y - half_size.y;
+        let min_x = area.min.x + half_size.x;
+        let max_x = area.max.x - half_size.x;
+
+        if position.x > max_x || position.x < min_x {
+            // x is out of border, we have to set the intent modifier such that
+            // it goes away from the edge after it is multiplied with the
+            // original intent FIXME: This is hacky, can we do better? The
+            // problem is how to combine arbitrary intent changes based on
+            // pattern and make it orthogonal to the bouncing behavior. Maybe
+            // each pattern should define its own bouncing.
+            self.intent_modifier.x = self.intent.x.signum() * -position.x.signum();
+        }
+        if position.y > max_y || position.y < min_y {
+            self.intent_modifier.y = self.intent.y.signum() * -position.y.signum();
+        }
+    }
 }
 
 impl Default for MovementController {
@@ -59,17 +101,39 @@ impl Default for MovementController {
             intent_modifier: Vec2::ONE,
             // 400 pixels per second is a nice default, but we can still vary this per character.
             max_speed: 400.0,
+            push: Vec2::ZERO,
         }
     }
 }
 
-fn apply_movement(
+/// Entities with this component don't move, regardless of their
+/// [`MovementController`].
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct MovementFrozen;
+
+/// Entities with this component move based on real time instead of virtual
+/// time, so they are not affected when the game is slowed down.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct UnscaledTime;
+
+pub(super) fn apply_movement(
     time: Res<Time>,
-    mut movement_query: Query<(&MovementController, &mut Transform)>,
+    real_time: Res<Time<Real>>,
+    mut movement_query: Query<
+        (&MovementController, &mut Transform, Has<UnscaledTime>),
+        Without<MovementFrozen>,
+    >,
 ) {
-    for (controller, mut transform) in &mut movement_query {
-        let velocity = controller.max_speed * controller.intent * controller.intent_modifier;
-        transform.translation += velocity.extend(0.0) * time.delta_seconds();
+    for (controller, mut transform, unscaled) in &mut movement_query {
+        let velocity = controller.velocity();
+        let delta = if unscaled {
+            real_time.delta_seconds()
+        } else {
+            time.delta_seconds()
+        };
+        transform.translation += velocity.extend(0.0) * delta;
     }
 }
 
@@ -77,46 +141,94 @@ fn apply_movement(
 #[reflect(Component)]
 pub struct ScreenWrap;
 
-fn apply_screen_wrap(
+pub(super) fn apply_screen_wrap(
     gameplay_area: Res<GameplayArea>,
     mut wrap_query: Query<&mut Transform, With<ScreenWrap>>,
 ) {
-    let size = gameplay_area.main_area.size() + 256.0;
-    let half_size = size / 2.0;
     for mut transform in &mut wrap_query {
-        let position = transform.translation.xy();
-        let wrapped = (position + half_size).rem_euclid(size) - half_size;
+        let wrapped = wrap_position(gameplay_area.main_area, transform.translation.xy());
         transform.translation = wrapped.extend(transform.translation.z);
     }
 }
 
+/// Wrap `position` around `area`. There is some room outside of the area, so
+/// that entities are fully out of view before they appear on the other side.
+pub fn wrap_position(area: Rect, position: Vec2) -> Vec2 {
+    let size = area.size() + 256.0;
+    let half_size = size / 2.0;
+    (position + half_size).rem_euclid(size) - half_size
+}
+
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub struct ScreenBounce;
 
-fn apply_screen_bounce(
+pub(super) fn apply_screen_bounce(
     gameplay_area: Res<GameplayArea>,
-    mut query: Query<(&mut MovementController, &Transform, &CreatureImage), With<ScreenBounce>>,
+    mut query: Query<
+        (
+            &mut MovementController,
+            &Transform,
+            &CreatureImage,
+            &CreatureScale,
+        ),
+        With<ScreenBounce>,
+    >,
 ) {
-    for (mut movement, transform, image) in &mut query {
-        let half_image_size = image.size().as_vec2() * image.default_scale() / 2.0;
-        let min_y = gameplay_area.main_area.min.y + half_image_size.y;
-        let max_y = gameplay_area.main_area.max.y - half_image_size.y;
-        let min_x = gameplay_area.main_area.min.x + half_image_size.x;
-        let max_x = gameplay_area.main_area.max.x - half_image_size.x;
-
-        let position = transform.translation.xy();
-        if position.x > max_x || position.x < min_x {
-            // x is out of border, we have to set the intent modifier such that
-            // it goes away from the edge after it is multiplied with the
-            // original intent FIXME: This is hacky, can we do better? The
-            // problem is how to combine arbitrary intent changes based on
-            // pattern and make it orthogonal to the bouncing behavior. Maybe
-            // each pattern should define its own bouncing.
-            movement.intent_modifier.x = movement.intent.x.signum() * -position.x.signum();
+    for (mut movement, transform, image, scale) in &mut query {
+        let half_image_size = image.size().as_vec2() * scale.0 / 2.0;
+        movement.bounce(
+            gameplay_area.main_area,
+            half_image_size,
+            transform.translation.xy(),
+        );
+    }
+}
+
+/// How an entity behaves at the edges of the gameplay area.
+#[derive(Debug, Clone, Copy)]
+pub enum EdgeBehavior {
+    /// See [`ScreenWrap`].
+    Wrap,
+    /// See [`ScreenBounce`].
+    Bounce { half_size: Vec2 },
+}
+
+/// Predict the positions of an entity over the next `duration`, by running the
+/// same steps as the movement systems with a fixed time step of `step`.
+pub fn predict_path(
+    mut pattern: MovementPattern,
+    mut controller: MovementController,
+    start: Vec2,
+    edge: EdgeBehavior,
+    area: Rect,
+    duration: Duration,
+    step: Duration,
+) -> Vec<Vec2> {
+    let mut position = start;
+    let mut path = vec![start];
+    let steps = (duration.as_secs_f32() / step.as_secs_f32()) as usize;
+    for _ in 0..steps {
+        pattern.tick(step);
+        controller.intent = pattern.intent();
+        position += controller.velocity() * step.as_secs_f32();
+        match edge {
+            EdgeBehavior::Wrap => position = wrap_position(area, position),
+            EdgeBehavior::Bounce { half_size } => controller.bounce(area, half_size, position),
         }
-        if position.y > max_y || position.y < min_y {
-            movement.intent_modifier.y = movement.intent.y.signum() * -position.y.signum();
+        path.push(position);
+    }
+    path
+}
+
+/// Draw `path` as lines, without connecting points where an entity wrapped
+/// around the screen.
+pub fn draw_path(gizmos: &mut Gizmos, path: &[Vec2], color: Color) {
+    let mut start = 0;
+    for end in 1..=path.len() {
+        if end == path.len() || path[end].distance(path[end - 1]) > 100.0 {
+            gizmos.linestrip_2d(path[start..end].iter().copied(), color);
+            start = end;
         }
     }
 }
@@ -21,7 +21,7 @@ pub enum MovementPattern {
     Circle { timer: Timer, radius: f32 },
 }
 
-#[derive(Debug, Clone, Reflect, serde::Deserialize)]
+#[derive(Debug, Clone, Reflect, serde::Serialize, serde::Deserialize)]
 pub enum MovementPatternDefinition {
     Constant { speed: Vec2 },
     Periodic { duration_ms: u64, max_speed: Vec2 },
@@ -39,36 +39,48 @@ pub(super) fn plugin(app: &mut App) {
     );
 }
 
-fn move_creatures(mut controller_query: Query<(&mut MovementController, &MovementPattern)>) {
+pub(super) fn move_creatures(
+    mut controller_query: Query<(&mut MovementController, &MovementPattern)>,
+) {
     for (mut movement_controller, creature_property) in &mut controller_query {
-        match creature_property {
-            MovementPattern::Constant { speed } => {
-                movement_controller.intent = *speed;
-            }
+        movement_controller.intent = creature_property.intent();
+    }
+}
+
+pub(super) fn update_timer(time: Res<Time>, mut query: Query<&mut MovementPattern>) {
+    let delta = time.delta();
+    for mut pattern in &mut query {
+        pattern.tick(delta);
+    }
+}
+
+impl MovementPattern {
+    /// The direction the pattern moves in at this point in time, see
+    /// [`MovementController::intent`].
+    pub fn intent(&self) -> Vec2 {
+        match self {
+            MovementPattern::Constant { speed } => *speed,
             MovementPattern::Periodic { max_speed, timer } => {
                 // use positive range of sinus for the speed
                 let fraction = f32::sin(f32::consts::PI * timer.fraction());
-                movement_controller.intent = *max_speed * fraction;
+                *max_speed * fraction
             }
             MovementPattern::Circle { timer, radius } => {
                 // use a constant speed to go around the circle
                 let angle = timer.fraction() * f32::consts::TAU;
-                movement_controller.intent = Vec2 {
+                Vec2 {
                     x: -f32::sin(angle) * radius,
                     y: f32::cos(angle) * radius,
-                };
+                }
             }
         }
     }
-}
 
-fn update_timer(time: Res<Time>, mut query: Query<&mut MovementPattern>) {
-    let delta = time.delta();
-    for mut pattern in &mut query {
-        match *pattern {
+    /// Advance the pattern in time.
+    pub fn tick(&mut self, delta: Duration) {
+        match self {
             MovementPattern::Constant { .. } => (),
-            MovementPattern::Periodic { ref mut timer, .. }
-            | MovementPattern::Circle { ref mut timer, .. } => {
+            MovementPattern::Periodic { timer, .. } | MovementPattern::Circle { timer, .. } => {
                 timer.tick(delta);
             }
         }
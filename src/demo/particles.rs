@@ -0,0 +1,367 @@
+//! Sprite particles, defined in `assets/particles.ron`.
+//!
+//! Every particle effect there has a name. Spawn a burst of an effect with
+//! the [`EmitParticles`] command, or add a [`ParticleEmitter`] to an entity to
+//! keep emitting particles where it is, e.g. for trails. Nothing is emitted if
+//! particles are turned off in the
+//! [`EffectSettings`](super::juice::EffectSettings).
+//!
+//! Particle entities are pooled: dead particles are released into the
+//! [`Pool`] and reused by the next emission instead of being despawned.
+
+use std::{collections::HashMap, sync::Arc};
+
+use bevy::{
+    ecs::{entity::Entities, system::RunSystemOnce as _, world::Command},
+    prelude::*,
+};
+use bevy_common_assets::ron::RonAssetPlugin;
+use rand::{rngs::ThreadRng, Rng};
+
+use crate::{
+    asset_tracking::LoadResource,
+    pool::{Pool, PoolApp, Release},
+    save::SaveData,
+    screens::{GameplayArea, Screen},
+    AppSet,
+};
+
+use super::events::CreatureKilled;
+
+/// Particles alive at once. Emissions beyond this are dropped.
+const MAX_PARTICLES: usize = 1024;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(Particle, ParticleEmitter)>();
+    app.add_plugins(RonAssetPlugin::<ParticleEffects>::new(&["particles.ron"]));
+    app.load_resource::<ParticleAssets>();
+    // Released particles are hidden and do nothing else.
+    app.init_pool::<Particle>(|_| ());
+    app.add_systems(OnEnter(Screen::Gameplay), spawn_ambience);
+    app.add_systems(OnExit(Screen::Gameplay), release_all_particles);
+    app.add_systems(
+        Update,
+        (
+            tick_particles.in_set(AppSet::TickTimers),
+            (run_emitters, poof_killed_creatures, update_particles)
+                .chain()
+                .run_if(resource_exists::<ParticleAssets>)
+                .in_set(AppSet::Update),
+        ),
+    );
+}
+
+/// All particle effects, by name.
+#[derive(Debug, Clone, Asset, Reflect, serde::Deserialize)]
+pub struct ParticleEffects {
+    /// Shared with the particles, which sample their curves over their
+    /// lifetime.
+    pub effects: HashMap<String, Arc<ParticleEffect>>,
+}
+
+#[derive(Debug, Clone, Reflect, serde::Deserialize)]
+pub struct ParticleEffect {
+    /// The image of the particles, relative to `assets/`.
+    pub texture: String,
+    pub emission: Emission,
+    /// Particles start at a random point of a rectangle of this size around
+    /// the emitter.
+    #[serde(default)]
+    pub area: (f32, f32),
+    /// The range of angles the particles fly at, in degrees. 0 is to the
+    /// right, 90 is up.
+    pub angle: (f32, f32),
+    /// Spread the particles of a burst evenly over the angles instead of
+    /// randomly.
+    #[serde(default)]
+    pub even: bool,
+    /// The range of speeds, in world units per second.
+    pub speed: (f32, f32),
+    /// The range of lifetimes, in seconds.
+    pub lifetime: (f32, f32),
+    /// Acceleration in world units per second squared.
+    #[serde(default)]
+    pub gravity: (f32, f32),
+    /// The opacity over the lifetime. The values are spread evenly from the
+    /// start to the end and interpolated in between.
+    #[serde(default)]
+    pub alpha: Vec<f32>,
+    /// The scale over the lifetime, like `alpha`.
+    #[serde(default)]
+    pub scale: Vec<f32>,
+    #[serde(default)]
+    pub z: f32,
+}
+
+/// How an effect emits its particles.
+#[derive(Debug, Clone, Copy, Reflect, serde::Deserialize)]
+pub enum Emission {
+    /// This many particles at once.
+    Burst(u32),
+    /// Particles per second, while a [`ParticleEmitter`] exists.
+    Continuous(f32),
+}
+
+#[derive(Resource, Asset, Reflect, Clone)]
+pub struct ParticleAssets {
+    #[dependency]
+    pub effects: Handle<ParticleEffects>,
+}
+
+impl FromWorld for ParticleAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            effects: assets.load("particles.ron"),
+        }
+    }
+}
+
+/// Spawn a burst of the effect named `effect` at a position.
+pub struct EmitParticles {
+    pub effect: String,
+    pub position: Vec2,
+}
+
+impl Command for EmitParticles {
+    fn apply(self, world: &mut World) {
+        world.run_system_once_with((self.effect, self.position), emit_burst);
+    }
+}
+
+/// Keeps emitting the effect named `effect` at the entity's position.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct ParticleEmitter {
+    pub effect: String,
+    /// Particles that are due but not emitted yet, as a fraction.
+    pending: f32,
+}
+
+impl ParticleEmitter {
+    pub fn new(effect: impl Into<String>) -> Self {
+        Self {
+            effect: effect.into(),
+            pending: 0.0,
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct Particle {
+    timer: Timer,
+    velocity: Vec2,
+    effect: Arc<ParticleEffect>,
+}
+
+/// Sample a curve of values spread evenly over `0..=1`.
+fn sample(curve: &[f32], t: f32) -> f32 {
+    match curve {
+        [] => 1.0,
+        [value] => *value,
+        _ => {
+            let position = t.clamp(0.0, 1.0) * (curve.len() - 1) as f32;
+            let index = (position as usize).min(curve.len() - 2);
+            curve[index].lerp(curve[index + 1], position - index as f32)
+        }
+    }
+}
+
+fn random_in(rng: &mut ThreadRng, (min, max): (f32, f32)) -> f32 {
+    if max > min {
+        rng.gen_range(min..=max)
+    } else {
+        min
+    }
+}
+
+/// Emit `count` particles of `effect`, reusing pooled entities if there are
+/// any. `active` is the number of particles alive.
+fn emit(
+    commands: &mut Commands,
+    pool: &mut Pool<Particle>,
+    entities: &Entities,
+    active: &mut usize,
+    asset_server: &AssetServer,
+    effect: &Arc<ParticleEffect>,
+    position: Vec2,
+    count: u32,
+) {
+    let texture: Handle<Image> = asset_server.load(&effect.texture);
+    let rng = &mut rand::thread_rng();
+    for i in 0..count {
+        if *active >= MAX_PARTICLES {
+            return;
+        }
+        *active += 1;
+
+        let angle = if effect.even {
+            let (min, max) = effect.angle;
+            min + (max - min) * i as f32 / count as f32
+        } else {
+            random_in(rng, effect.angle)
+        };
+        let (width, height) = effect.area;
+        let offset = Vec2::new(
+            random_in(rng, (-width / 2.0, width / 2.0)),
+            random_in(rng, (-height / 2.0, height / 2.0)),
+        );
+        let sprite = Sprite {
+            color: Color::WHITE.with_alpha(sample(&effect.alpha, 0.0)),
+            ..default()
+        };
+        let transform = Transform::from_translation((position + offset).extend(effect.z))
+            .with_scale(Vec3::splat(sample(&effect.scale, 0.0)));
+        let particle = Particle {
+            timer: Timer::from_seconds(random_in(rng, effect.lifetime), TimerMode::Once),
+            velocity: Vec2::from_angle(angle.to_radians()) * random_in(rng, effect.speed),
+            effect: Arc::clone(effect),
+        };
+        pool.spawn(commands, entities).insert((
+            Name::new("Particle"),
+            SpriteBundle {
+                texture: texture.clone(),
+                sprite,
+                transform,
+                ..default()
+            },
+            particle,
+        ));
+    }
+}
+
+fn emit_burst(
+    In((name, position)): In<(String, Vec2)>,
+    mut commands: Commands,
+    mut pool: ResMut<Pool<Particle>>,
+    entities: &Entities,
+    asset_server: Res<AssetServer>,
+    particle_assets: Option<Res<ParticleAssets>>,
+    effects: Res<Assets<ParticleEffects>>,
+    save: Res<SaveData>,
+    particles: Query<(), With<Particle>>,
+) {
+    if !save.effects.particles {
+        return;
+    }
+    let Some(effects) = particle_assets.and_then(|assets| effects.get(&assets.effects)) else {
+        return;
+    };
+    let Some(effect) = effects.effects.get(&name) else {
+        warn!("Unknown particle effect {name:?}");
+        return;
+    };
+    let count = match effect.emission {
+        Emission::Burst(count) => count,
+        // A burst of a continuous effect is a single particle.
+        Emission::Continuous(_) => 1,
+    };
+    emit(
+        &mut commands,
+        &mut pool,
+        entities,
+        &mut particles.iter().len(),
+        &asset_server,
+        effect,
+        position,
+        count,
+    );
+}
+
+fn run_emitters(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pool: ResMut<Pool<Particle>>,
+    entities: &Entities,
+    asset_server: Res<AssetServer>,
+    particle_assets: Res<ParticleAssets>,
+    effects: Res<Assets<ParticleEffects>>,
+    save: Res<SaveData>,
+    mut emitters: Query<(&mut ParticleEmitter, &GlobalTransform)>,
+    particles: Query<(), With<Particle>>,
+) {
+    if !save.effects.particles {
+        return;
+    }
+    let Some(effects) = effects.get(&particle_assets.effects) else {
+        return;
+    };
+    let mut active = particles.iter().len();
+    for (mut emitter, transform) in &mut emitters {
+        let Some(effect) = effects.effects.get(&emitter.effect) else {
+            continue;
+        };
+        let Emission::Continuous(rate) = effect.emission else {
+            continue;
+        };
+        emitter.pending += rate * time.delta_seconds();
+        let count = emitter.pending as u32;
+        emitter.pending -= count as f32;
+        if count > 0 {
+            emit(
+                &mut commands,
+                &mut pool,
+                entities,
+                &mut active,
+                &asset_server,
+                effect,
+                transform.translation().xy(),
+                count,
+            );
+        }
+    }
+}
+
+fn poof_killed_creatures(mut commands: Commands, mut events: EventReader<CreatureKilled>) {
+    for event in events.read() {
+        commands.add(EmitParticles {
+            effect: "poof".to_string(),
+            position: event.position,
+        });
+    }
+}
+
+/// Drifting dust over the whole playfield.
+fn spawn_ambience(mut commands: Commands, gameplay_area: Option<Res<GameplayArea>>) {
+    let center = gameplay_area.map_or(Vec2::ZERO, |area| area.main_area.center());
+    commands.spawn((
+        Name::new("Ambience"),
+        SpatialBundle::from_transform(Transform::from_translation(center.extend(0.0))),
+        ParticleEmitter::new("ambience"),
+        StateScoped(Screen::Gameplay),
+    ));
+}
+
+fn tick_particles(time: Res<Time>, mut query: Query<&mut Particle>) {
+    for mut particle in &mut query {
+        particle.timer.tick(time.delta());
+    }
+}
+
+fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut particle, mut transform, mut sprite) in &mut query {
+        if particle.timer.finished() {
+            commands.add(Release::<Particle>::new(entity));
+            continue;
+        }
+        let gravity = Vec2::from(particle.effect.gravity);
+        particle.velocity += gravity * delta;
+        transform.translation += particle.velocity.extend(0.0) * delta;
+
+        let life = particle.timer.fraction();
+        sprite.color.set_alpha(sample(&particle.effect.alpha, life));
+        transform.scale = Vec3::splat(sample(&particle.effect.scale, life));
+    }
+}
+
+fn release_all_particles(mut commands: Commands, query: Query<Entity, With<Particle>>) {
+    for entity in &query {
+        commands.add(Release::<Particle>::new(entity));
+    }
+}
@@ -0,0 +1,231 @@
+//! Local multiplayer: up to [`MAX_PLAYERS`] players on the same screen at
+//! once.
+//!
+//! The first player always uses the mouse. Other players join with the
+//! keyboard or a gamepad and aim with a reticle. Every player has their own
+//! bullets, ball color and score.
+
+use bevy::{
+    input::gamepad::{GamepadAxisType, GamepadButtonType},
+    prelude::*,
+};
+
+use crate::{
+    screens::{GameplayArea, Screen},
+    AppSet,
+};
+
+use super::creature::{CreatureAssets, ThrowBall};
+
+pub const MAX_PLAYERS: usize = 4;
+/// The tint of each player's balls and reticle. The first player keeps the
+/// original look of the single player game.
+pub const PLAYER_COLORS: [Color; MAX_PLAYERS] = [
+    Color::WHITE,
+    Color::srgb(1.0, 0.45, 0.45),
+    Color::srgb(0.45, 0.65, 1.0),
+    Color::srgb(0.5, 1.0, 0.5),
+];
+/// How fast reticles move, in world units per second.
+const RETICLE_SPEED: f32 = 600.0;
+/// Gamepad stick values below this are ignored.
+const STICK_DEAD_ZONE: f32 = 0.15;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(PlayerId, Players)>();
+    app.init_resource::<Players>();
+    // Menus outside the party setup are single player.
+    app.add_systems(OnEnter(Screen::Title), reset_players);
+    app.add_systems(OnEnter(Screen::Gameplay), spawn_reticles);
+    app.add_systems(
+        Update,
+        (move_reticles, throw_from_reticles)
+            .chain()
+            .in_set(AppSet::RecordInput)
+            .run_if(in_state(Screen::Gameplay).and_then(resource_exists::<GameplayArea>)),
+    );
+}
+
+/// The player that something belongs to, like a [`Bullet`](super::creature::Bullet).
+/// Players are counted from 0.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Reflect)]
+#[reflect(Component)]
+pub struct PlayerId(pub usize);
+
+impl PlayerId {
+    pub fn color(&self) -> Color {
+        PLAYER_COLORS[self.0 % MAX_PLAYERS]
+    }
+
+    /// The name shown to players, counted from 1.
+    pub fn name(&self) -> String {
+        format!("P{}", self.0 + 1)
+    }
+}
+
+/// How a player aims and throws.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum PlayerInput {
+    /// Aim with the mouse or touch and click to throw.
+    Mouse,
+    /// Aim with the arrow keys and throw with Enter.
+    Keyboard,
+    /// Aim with the left stick and throw with the south button.
+    Gamepad(Gamepad),
+}
+
+impl PlayerInput {
+    pub fn name(&self) -> String {
+        match self {
+            PlayerInput::Mouse => "Mouse".to_string(),
+            PlayerInput::Keyboard => "Keyboard".to_string(),
+            PlayerInput::Gamepad(gamepad) => format!("Gamepad {}", gamepad.id + 1),
+        }
+    }
+}
+
+/// The players taking part, indexed by [`PlayerId`].
+#[derive(Resource, Debug, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct Players(pub Vec<PlayerInput>);
+
+impl Default for Players {
+    fn default() -> Self {
+        Self(vec![PlayerInput::Mouse])
+    }
+}
+
+impl Players {
+    pub fn is_multiplayer(&self) -> bool {
+        self.0.len() > 1
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = PlayerId> {
+        (0..self.0.len()).map(PlayerId)
+    }
+
+    /// Add a player with `input`, unless the game is full or the input is
+    /// already taken.
+    pub fn join(&mut self, input: PlayerInput) -> Option<PlayerId> {
+        if self.0.len() >= MAX_PLAYERS || self.0.contains(&input) {
+            return None;
+        }
+        self.0.push(input);
+        Some(PlayerId(self.0.len() - 1))
+    }
+}
+
+/// Shows where a player that doesn't use the mouse aims.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+struct Reticle {
+    input: PlayerInput,
+}
+
+fn reset_players(mut players: ResMut<Players>) {
+    *players = Players::default();
+}
+
+fn spawn_reticles(
+    mut commands: Commands,
+    players: Res<Players>,
+    creature_assets: Option<Res<CreatureAssets>>,
+) {
+    let Some(creature_assets) = creature_assets else {
+        return;
+    };
+    for (player, input) in players.ids().zip(players.0.iter().copied()) {
+        if input == PlayerInput::Mouse {
+            // The mouse player uses the game cursor.
+            continue;
+        }
+        commands.spawn((
+            Name::new("Reticle"),
+            SpriteBundle {
+                texture: creature_assets.ball.clone(),
+                sprite: Sprite {
+                    color: player.color().with_alpha(0.6),
+                    ..default()
+                },
+                // Spread the players over the screen.
+                transform: Transform::from_xyz((player.0 as f32 - 1.5) * 120.0, 0.0, 3.0),
+                ..default()
+            },
+            Reticle { input },
+            player,
+            StateScoped(Screen::Gameplay),
+        ));
+    }
+}
+
+fn move_reticles(
+    time: Res<Time<Real>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    axes: Res<Axis<GamepadAxis>>,
+    gameplay_area: Res<GameplayArea>,
+    mut reticles: Query<(&Reticle, &mut Transform)>,
+) {
+    for (reticle, mut transform) in &mut reticles {
+        let direction = match reticle.input {
+            PlayerInput::Mouse => continue,
+            PlayerInput::Keyboard => {
+                let mut direction = Vec2::ZERO;
+                if keys.pressed(KeyCode::ArrowLeft) {
+                    direction.x -= 1.0;
+                }
+                if keys.pressed(KeyCode::ArrowRight) {
+                    direction.x += 1.0;
+                }
+                if keys.pressed(KeyCode::ArrowDown) {
+                    direction.y -= 1.0;
+                }
+                if keys.pressed(KeyCode::ArrowUp) {
+                    direction.y += 1.0;
+                }
+                direction.normalize_or_zero()
+            }
+            PlayerInput::Gamepad(gamepad) => {
+                let axis = |axis_type| {
+                    axes.get(GamepadAxis::new(gamepad, axis_type))
+                        .unwrap_or_default()
+                };
+                let stick = Vec2::new(
+                    axis(GamepadAxisType::LeftStickX),
+                    axis(GamepadAxisType::LeftStickY),
+                );
+                if stick.length() < STICK_DEAD_ZONE {
+                    Vec2::ZERO
+                } else {
+                    stick.clamp_length_max(1.0)
+                }
+            }
+        };
+        let area = gameplay_area.main_area;
+        let position =
+            transform.translation.xy() + direction * RETICLE_SPEED * time.delta_seconds();
+        let position = position.clamp(area.min, area.max);
+        transform.translation = position.extend(transform.translation.z);
+    }
+}
+
+fn throw_from_reticles(
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    reticles: Query<(&Reticle, &PlayerId, &Transform)>,
+    mut throws: EventWriter<ThrowBall>,
+) {
+    for (reticle, player, transform) in &reticles {
+        let pressed = match reticle.input {
+            PlayerInput::Mouse => false,
+            PlayerInput::Keyboard => keys.just_pressed(KeyCode::Enter),
+            PlayerInput::Gamepad(gamepad) => {
+                gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::South))
+            }
+        };
+        if pressed {
+            throws.send(ThrowBall {
+                player: *player,
+                target: transform.translation.xy(),
+            });
+        }
+    }
+}
@@ -0,0 +1,343 @@
+//! Power-ups that float across the field like creatures and are activated by
+//! hitting them with a ball.
+
+use std::time::Duration;
+
+use bevy::{
+    ecs::{system::RunSystemOnce as _, world::Command},
+    prelude::*,
+};
+
+use crate::{
+    audio::SoundEffect,
+    demo::{
+        creature::{Bullet, CreatureAssets},
+        level::WaveTimer,
+        movement::{MovementController, MovementFrozen, ScreenWrap},
+        movement_pattern::MovementPatternDefinition,
+    },
+    screens::Screen,
+    AppSet,
+};
+
+use super::creature::Creature;
+
+/// How long a power-up floats around before it disappears.
+const POWER_UP_LIFETIME: Duration = Duration::from_secs(8);
+/// Size of the hitbox of a power-up in pixels.
+const POWER_UP_SIZE: f32 = 64.0;
+/// Speed of the virtual time while [`PowerUpKind::SlowTime`] is active.
+const SLOW_TIME_SPEED: f32 = 0.5;
+/// The time added to the wave by [`PowerUpKind::ExtraTime`].
+const EXTRA_TIME: Duration = Duration::from_secs(5);
+/// Number of balls thrown at once while [`PowerUpKind::MultiBall`] is active.
+pub const MULTI_BALL_COUNT: usize = 3;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(PowerUp, ActivePowerUps)>();
+    app.init_resource::<ActivePowerUps>();
+    app.add_systems(OnExit(Screen::Gameplay), reset_power_ups);
+
+    app.add_systems(
+        Update,
+        (
+            (tick_power_ups, tick_power_up_spawners).in_set(AppSet::TickTimers),
+            (collect_power_ups, despawn_expired_power_ups, apply_freeze)
+                .chain()
+                .run_if(resource_exists::<CreatureAssets>)
+                .in_set(AppSet::Update),
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect, serde::Serialize, serde::Deserialize,
+)]
+pub enum PowerUpKind {
+    /// Slows down time for everything but the ball.
+    SlowTime,
+    /// Stops all creatures in place.
+    Freeze,
+    /// Throw several balls at once.
+    MultiBall,
+    /// Adds time to the current wave.
+    ExtraTime,
+}
+
+impl PowerUpKind {
+    /// How long the effect lasts once collected. Zero for instant effects.
+    pub fn duration(&self) -> Duration {
+        match self {
+            PowerUpKind::SlowTime => Duration::from_secs(5),
+            PowerUpKind::Freeze => Duration::from_secs(3),
+            PowerUpKind::MultiBall => Duration::from_secs(8),
+            PowerUpKind::ExtraTime => Duration::ZERO,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PowerUpKind::SlowTime => "Slow",
+            PowerUpKind::Freeze => "Freeze",
+            PowerUpKind::MultiBall => "Multi",
+            PowerUpKind::ExtraTime => "+5s",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            PowerUpKind::SlowTime => Color::srgb(0.4, 0.4, 1.0),
+            PowerUpKind::Freeze => Color::srgb(0.6, 1.0, 1.0),
+            PowerUpKind::MultiBall => Color::srgb(1.0, 0.5, 0.0),
+            PowerUpKind::ExtraTime => Color::srgb(0.3, 1.0, 0.3),
+        }
+    }
+}
+
+/// A power-up in a level, loaded as part of a `LevelDefinition`.
+#[derive(Debug, Clone, Reflect, serde::Serialize, serde::Deserialize)]
+pub struct PowerUpDefinition {
+    pub kind: PowerUpKind,
+    /// Time after the start of the wave until the power-up appears.
+    #[serde(default)]
+    pub delay_ms: u64,
+    pub max_speed: f32,
+    /// None is turned into a random position on screen
+    pub pos: Option<Vec2>,
+    pub movement: MovementPatternDefinition,
+}
+
+/// A power-up floating across the field that can be collected.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct PowerUp {
+    pub kind: PowerUpKind,
+    lifetime: Timer,
+}
+
+/// The power-ups that have been collected and are still in effect.
+#[derive(Resource, Debug, Clone, Default, Reflect)]
+#[reflect(Resource)]
+pub struct ActivePowerUps(pub Vec<(PowerUpKind, Timer)>);
+
+impl ActivePowerUps {
+    pub fn is_active(&self, kind: PowerUpKind) -> bool {
+        self.0.iter().any(|(active, _)| *active == kind)
+    }
+}
+
+/// A command to spawn a power-up.
+#[derive(Debug)]
+pub struct SpawnPowerUp {
+    pub kind: PowerUpKind,
+    /// See [`MovementController::max_speed`].
+    pub max_speed: f32,
+    pub pos: Vec2,
+    pub movement: MovementPatternDefinition,
+    /// The power-up appears after this delay.
+    pub delay: Duration,
+}
+
+impl Command for SpawnPowerUp {
+    fn apply(self, world: &mut World) {
+        world.run_system_once_with(self, spawn_power_up);
+    }
+}
+
+/// Waits for the delay of a [`SpawnPowerUp`] before spawning it.
+#[derive(Component)]
+pub struct PowerUpSpawner {
+    timer: Timer,
+    spawn: Option<SpawnPowerUp>,
+}
+
+fn spawn_power_up(
+    In(config): In<SpawnPowerUp>,
+    mut commands: Commands,
+    creature_assets: Res<CreatureAssets>,
+) {
+    if !config.delay.is_zero() {
+        commands.spawn((
+            Name::new("Power-up Spawner"),
+            PowerUpSpawner {
+                timer: Timer::new(config.delay, TimerMode::Once),
+                spawn: Some(SpawnPowerUp {
+                    delay: Duration::ZERO,
+                    ..config
+                }),
+            },
+            StateScoped(Screen::Gameplay),
+        ));
+        return;
+    }
+
+    commands.spawn((
+        Name::new("Power-up"),
+        PowerUp {
+            kind: config.kind,
+            lifetime: Timer::new(POWER_UP_LIFETIME, TimerMode::Once),
+        },
+        SpriteBundle {
+            texture: creature_assets.ball.clone(),
+            sprite: Sprite {
+                color: config.kind.color(),
+                custom_size: Some(Vec2::splat(POWER_UP_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_translation(config.pos.extend(1.5)),
+            ..default()
+        },
+        MovementController {
+            max_speed: config.max_speed,
+            ..default()
+        },
+        config.movement.build(),
+        ScreenWrap,
+        StateScoped(Screen::Gameplay),
+    ));
+}
+
+fn tick_power_up_spawners(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut spawners: Query<(Entity, &mut PowerUpSpawner)>,
+) {
+    for (entity, mut spawner) in &mut spawners {
+        if spawner.timer.tick(time.delta()).finished() {
+            if let Some(spawn) = spawner.spawn.take() {
+                commands.add(spawn);
+            }
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Power-up effects run on real time so that slowing down time doesn't make
+/// them last longer.
+fn tick_power_ups(
+    time: Res<Time<Real>>,
+    mut active: ResMut<ActivePowerUps>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut power_ups: Query<&mut PowerUp>,
+) {
+    for mut power_up in &mut power_ups {
+        power_up.lifetime.tick(time.delta());
+    }
+
+    let was_slow = active.is_active(PowerUpKind::SlowTime);
+    for (_, timer) in &mut active.0 {
+        timer.tick(time.delta());
+    }
+    active.0.retain(|(_, timer)| !timer.finished());
+    if was_slow && !active.is_active(PowerUpKind::SlowTime) {
+        virtual_time.set_relative_speed(1.0);
+    }
+}
+
+fn collect_power_ups(
+    mut commands: Commands,
+    bullets: Query<(&Bullet, &Transform)>,
+    power_ups: Query<(Entity, &PowerUp, &Transform)>,
+    mut active: ResMut<ActivePowerUps>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut wave_timer: Option<ResMut<WaveTimer>>,
+    creature_assets: Res<CreatureAssets>,
+) {
+    let mut collected = Vec::new();
+    for (bullet, bullet_transform) in &bullets {
+        if !bullet.timer.just_finished() {
+            continue;
+        }
+        let landing = bullet_transform.translation.xy();
+        for (entity, power_up, transform) in &power_ups {
+            let bounding_box =
+                Rect::from_center_size(transform.translation.xy(), Vec2::splat(POWER_UP_SIZE));
+            if collected.contains(&entity) || !bounding_box.contains(landing) {
+                continue;
+            }
+            collected.push(entity);
+            commands.entity(entity).despawn();
+            commands.spawn((
+                AudioBundle {
+                    source: creature_assets.catch.clone(),
+                    settings: PlaybackSettings::DESPAWN,
+                },
+                SoundEffect,
+            ));
+            activate(
+                power_up.kind,
+                &mut active,
+                &mut virtual_time,
+                wave_timer.as_deref_mut(),
+            );
+        }
+    }
+}
+
+/// Apply the effect of a collected power-up.
+pub fn activate(
+    kind: PowerUpKind,
+    active: &mut ActivePowerUps,
+    virtual_time: &mut Time<Virtual>,
+    wave_timer: Option<&mut WaveTimer>,
+) {
+    match kind {
+        PowerUpKind::SlowTime => virtual_time.set_relative_speed(SLOW_TIME_SPEED),
+        PowerUpKind::ExtraTime => {
+            if let Some(timer) = wave_timer {
+                if !timer.0.finished() {
+                    let elapsed = timer.0.elapsed().saturating_sub(EXTRA_TIME);
+                    timer.0.set_elapsed(elapsed);
+                }
+            }
+            return;
+        }
+        PowerUpKind::Freeze | PowerUpKind::MultiBall => (),
+    }
+
+    // Collecting the same power-up again restarts its duration.
+    let timer = Timer::new(kind.duration(), TimerMode::Once);
+    if let Some((_, active_timer)) = active.0.iter_mut().find(|(active, _)| *active == kind) {
+        *active_timer = timer;
+    } else {
+        active.0.push((kind, timer));
+    }
+}
+
+fn despawn_expired_power_ups(
+    mut commands: Commands,
+    mut power_ups: Query<(Entity, &PowerUp, &mut Sprite)>,
+) {
+    for (entity, power_up, mut sprite) in &mut power_ups {
+        if power_up.lifetime.finished() {
+            commands.entity(entity).despawn();
+        } else if power_up.lifetime.remaining_secs() < 1.0 {
+            // fade out during the last second
+            sprite.color.set_alpha(power_up.lifetime.remaining_secs());
+        }
+    }
+}
+
+fn apply_freeze(
+    mut commands: Commands,
+    active: Res<ActivePowerUps>,
+    creatures: Query<(Entity, Has<MovementFrozen>), With<Creature>>,
+) {
+    let freeze = active.is_active(PowerUpKind::Freeze);
+    for (entity, frozen) in &creatures {
+        if freeze && !frozen {
+            commands.entity(entity).insert(MovementFrozen);
+        } else if !freeze && frozen {
+            commands.entity(entity).remove::<MovementFrozen>();
+        }
+    }
+}
+
+pub(super) fn reset_power_ups(
+    mut active: ResMut<ActivePowerUps>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+) {
+    active.0.clear();
+    virtual_time.set_relative_speed(1.0);
+}
@@ -0,0 +1,155 @@
+//! Keeps the [`GameScore`] up to date.
+//!
+//! Killing creatures gives points. Consecutive hits build up a combo that
+//! multiplies the points, a miss resets it.
+//!
+//! In multiplayer games, every player also gets their own [`PlayerScores`]
+//! entry with points for the creatures they hit, multiplied by their own
+//! combo. The points are worked out when the creature is killed, see
+//! [`CreatureKilled`].
+
+use bevy::prelude::*;
+
+use crate::{
+    screens::{GameScore, Screen},
+    AppSet,
+};
+
+use super::{
+    creature::{kill_hit_creatures, process_bullets_landing},
+    events::{BulletLanded, CreatureKilled, ShotFired, WaveCleared, WaveStarted},
+    players::{PlayerId, Players},
+};
+
+/// Points for a killed creature, multiplied by the combo.
+const KILL_POINTS: u32 = 10;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<PlayerScores>();
+    app.add_systems(OnEnter(Screen::Gameplay), reset_player_scores);
+    app.add_systems(
+        Update,
+        (count_waves, count_shots, count_landings, score_kills)
+            .chain()
+            // Kills use the combo of the bullets that landed in the same frame.
+            .after(process_bullets_landing)
+            .before(kill_hit_creatures)
+            .in_set(AppSet::Update)
+            .run_if(resource_exists::<GameScore>),
+    );
+}
+
+/// Points for a killed creature at the given combo.
+pub fn kill_points(combo: u32) -> u32 {
+    KILL_POINTS * combo.max(1)
+}
+
+/// The score of each player, indexed by [`PlayerId`].
+#[derive(Resource, Debug, Clone, Default)]
+pub struct PlayerScores(pub Vec<PlayerScore>);
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlayerScore {
+    /// Points for creatures hit by the player's bullets.
+    pub points: u32,
+    /// Number of the player's bullets in a row that hit.
+    pub combo: u32,
+    pub shots: u32,
+    pub misses: u32,
+}
+
+impl PlayerScores {
+    pub fn get(&self, player: PlayerId) -> Option<&PlayerScore> {
+        self.0.get(player.0)
+    }
+
+    fn get_mut(&mut self, player: PlayerId) -> Option<&mut PlayerScore> {
+        self.0.get_mut(player.0)
+    }
+
+    /// The players with their scores, best first.
+    pub fn ranking(&self) -> Vec<(PlayerId, PlayerScore)> {
+        let mut ranking = self
+            .0
+            .iter()
+            .enumerate()
+            .map(|(player, score)| (PlayerId(player), *score))
+            .collect::<Vec<_>>();
+        ranking.sort_by_key(|(_, score)| std::cmp::Reverse(score.points));
+        ranking
+    }
+}
+
+fn reset_player_scores(players: Res<Players>, mut scores: ResMut<PlayerScores>) {
+    scores.0 = vec![PlayerScore::default(); players.0.len()];
+}
+
+fn count_waves(
+    mut started_events: EventReader<WaveStarted>,
+    mut cleared_events: EventReader<WaveCleared>,
+    mut game_score: ResMut<GameScore>,
+) {
+    for event in cleared_events.read() {
+        game_score.wave_times.push(event.time);
+    }
+    if let Some(event) = started_events.read().last() {
+        if game_score.first_wave == 0 {
+            game_score.first_wave = event.wave;
+        }
+        // All waves before this one were cleared, or skipped by starting later.
+        game_score.score = event.wave - 1;
+    }
+}
+
+fn count_shots(
+    mut events: EventReader<ShotFired>,
+    mut game_score: ResMut<GameScore>,
+    mut player_scores: ResMut<PlayerScores>,
+) {
+    for event in events.read() {
+        game_score.shots += 1;
+        if let Some(score) = player_scores.get_mut(event.player) {
+            score.shots += 1;
+        }
+    }
+}
+
+fn count_landings(
+    mut events: EventReader<BulletLanded>,
+    mut game_score: ResMut<GameScore>,
+    mut player_scores: ResMut<PlayerScores>,
+) {
+    for landing in events.read() {
+        let player_score = player_scores.get_mut(landing.player);
+        if landing.missed() {
+            game_score.misses += 1;
+            game_score.combo = 0;
+            if let Some(score) = player_score {
+                score.misses += 1;
+                score.combo = 0;
+            }
+        } else {
+            game_score.hits += 1;
+            game_score.combo += 1;
+            if let Some(score) = player_score {
+                score.combo += 1;
+            }
+        }
+    }
+}
+
+fn score_kills(
+    mut events: EventReader<CreatureKilled>,
+    mut game_score: ResMut<GameScore>,
+    mut player_scores: ResMut<PlayerScores>,
+) {
+    for event in events.read() {
+        game_score.points += event.points;
+        if let Some(score) = event
+            .player
+            .and_then(|player| player_scores.get_mut(player))
+        {
+            score.points += event.points;
+        }
+    }
+}
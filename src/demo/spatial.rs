@@ -0,0 +1,320 @@
+//! A uniform grid over the [`GameplayArea`] to find the creatures at a
+//! position without looking at all of them.
+//!
+//! The [`SpatialGrid`] holds the hitbox of every living creature. It is
+//! rebuilt every tick after the creatures moved, before bullets land.
+
+use bevy::prelude::*;
+
+use crate::{
+    playfield::PLAYFIELD_SIZE,
+    screens::{GameplayArea, Screen},
+    AppSet,
+};
+
+use super::{
+    creature::{hitbox, process_bullets_landing, Creature, DeathAnimation},
+    creature_image::CreatureImage,
+    difficulty::Difficulty,
+    movement::{apply_screen_bounce, apply_screen_wrap},
+};
+
+/// Width and height of a cell, about the size of a creature.
+const CELL_SIZE: f32 = 128.0;
+/// Room around the gameplay area that is covered by cells, where wrapping
+/// creatures are out of view.
+const MARGIN: f32 = 128.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<SpatialGrid>();
+    app.add_systems(OnExit(Screen::Gameplay), clear_spatial_grid);
+    app.add_systems(
+        Update,
+        update_spatial_grid
+            .after(apply_screen_wrap)
+            .after(apply_screen_bounce)
+            .before(process_bullets_landing)
+            .in_set(AppSet::Update),
+    );
+}
+
+/// Entities with their bounding boxes, sorted into cells.
+///
+/// Positions outside of the grid belong to the closest cell at the border,
+/// so every query finds everything, but entities far outside of the
+/// [`GameplayArea`] are slower to find.
+#[derive(Resource, Debug, Clone)]
+pub struct SpatialGrid {
+    bounds: Rect,
+    columns: usize,
+    rows: usize,
+    /// Indices into `entries`, by cell.
+    cells: Vec<Vec<usize>>,
+    entries: Vec<(Entity, Rect)>,
+}
+
+impl Default for SpatialGrid {
+    fn default() -> Self {
+        Self::new(Rect::from_center_size(Vec2::ZERO, PLAYFIELD_SIZE))
+    }
+}
+
+impl SpatialGrid {
+    /// An empty grid covering `area`.
+    pub fn new(area: Rect) -> Self {
+        let bounds = area.inflate(MARGIN);
+        let columns = (bounds.width() / CELL_SIZE).ceil().max(1.0) as usize;
+        let rows = (bounds.height() / CELL_SIZE).ceil().max(1.0) as usize;
+        Self {
+            bounds,
+            columns,
+            rows,
+            cells: vec![Vec::new(); columns * rows],
+            entries: Vec::new(),
+        }
+    }
+
+    /// Remove all entities, keeping the allocated cells.
+    pub fn clear(&mut self) {
+        for cell in &mut self.cells {
+            cell.clear();
+        }
+        self.entries.clear();
+    }
+
+    /// Add `entity` to all cells that `rect` touches.
+    pub fn insert(&mut self, entity: Entity, rect: Rect) {
+        let index = self.entries.len();
+        self.entries.push((entity, rect));
+        let (min_column, min_row) = self.cell(rect.min);
+        let (max_column, max_row) = self.cell(rect.max);
+        for row in min_row..=max_row {
+            for column in min_column..=max_column {
+                self.cells[row * self.columns + column].push(index);
+            }
+        }
+    }
+
+    /// Number of entities in the grid.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The entities whose bounding box contains `point`.
+    pub fn query_point(&self, point: Vec2) -> impl Iterator<Item = Entity> + '_ {
+        let (column, row) = self.cell(point);
+        self.cells[row * self.columns + column]
+            .iter()
+            .map(|&index| self.entries[index])
+            .filter(move |(_, rect)| rect.contains(point))
+            .map(|(entity, _)| entity)
+    }
+
+    /// The entities whose bounding box overlaps `rect`, edges included.
+    pub fn query_rect(&self, rect: Rect) -> impl Iterator<Item = Entity> + '_ {
+        self.overlapping(rect).map(|(entity, _)| entity)
+    }
+
+    /// The entities whose bounding box overlaps the circle.
+    pub fn query_circle(&self, center: Vec2, radius: f32) -> impl Iterator<Item = Entity> + '_ {
+        let bounds = Rect::from_center_half_size(center, Vec2::splat(radius));
+        self.overlapping(bounds)
+            .filter(move |(_, rect)| center.clamp(rect.min, rect.max).distance(center) <= radius)
+            .map(|(entity, _)| entity)
+    }
+
+    /// The entries that overlap `rect`, each only once.
+    fn overlapping(&self, rect: Rect) -> impl Iterator<Item = (Entity, Rect)> + '_ {
+        let (min_column, min_row) = self.cell(rect.min);
+        let (max_column, max_row) = self.cell(rect.max);
+        (min_row..=max_row)
+            .flat_map(move |row| (min_column..=max_column).map(move |column| (column, row)))
+            .flat_map(move |cell| {
+                self.cells[cell.1 * self.columns + cell.0]
+                    .iter()
+                    .map(move |&index| (cell, self.entries[index]))
+            })
+            .filter(move |(cell, (_, other))| {
+                // An entry is in all cells it touches. Only take it from the
+                // cell with the lowest corner of the overlap.
+                overlaps(rect, *other) && self.cell(rect.min.max(other.min)) == *cell
+            })
+            .map(|(_, entry)| entry)
+    }
+
+    /// The column and row of the cell that `point` belongs to.
+    fn cell(&self, point: Vec2) -> (usize, usize) {
+        // Negative values saturate to 0.
+        let cell = (point - self.bounds.min) / CELL_SIZE;
+        (
+            (cell.x as usize).min(self.columns - 1),
+            (cell.y as usize).min(self.rows - 1),
+        )
+    }
+}
+
+/// Whether `a` and `b` overlap, edges included.
+fn overlaps(a: Rect, b: Rect) -> bool {
+    a.min.cmple(b.max).all() && b.min.cmple(a.max).all()
+}
+
+pub(super) fn update_spatial_grid(
+    mut grid: ResMut<SpatialGrid>,
+    gameplay_area: Option<Res<GameplayArea>>,
+    difficulty: Res<Difficulty>,
+    creatures: Query<
+        (Entity, &Transform, &CreatureImage),
+        (With<Creature>, Without<DeathAnimation>),
+    >,
+) {
+    match gameplay_area {
+        Some(area) if area.is_changed() => *grid = SpatialGrid::new(area.main_area),
+        _ => grid.clear(),
+    }
+    for (entity, transform, image) in &creatures {
+        grid.insert(entity, hitbox(image, transform, &difficulty));
+    }
+}
+
+fn clear_spatial_grid(mut grid: ResMut<SpatialGrid>) {
+    grid.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    const AREA: Rect = Rect {
+        min: Vec2::new(-640.0, -360.0),
+        max: Vec2::new(640.0, 360.0),
+    };
+    /// Queries and boxes go this far past the area, beyond the margin.
+    const OUTSIDE: f32 = 600.0;
+
+    /// A grid with boxes of all sizes, some of them reaching far out of the
+    /// grid, and the same boxes in a list.
+    fn random_grid(rng: &mut StdRng) -> (SpatialGrid, Vec<(Entity, Rect)>) {
+        let mut grid = SpatialGrid::new(AREA);
+        let entries: Vec<_> = (0..500)
+            .map(|i| {
+                let size = Vec2::new(rng.gen_range(0.0..400.0), rng.gen_range(0.0..400.0));
+                let rect = Rect::from_center_size(random_point(rng), size);
+                (Entity::from_raw(i), rect)
+            })
+            .collect();
+        for (entity, rect) in &entries {
+            grid.insert(*entity, *rect);
+        }
+        (grid, entries)
+    }
+
+    fn random_point(rng: &mut StdRng) -> Vec2 {
+        let bounds = AREA.inflate(OUTSIDE);
+        Vec2::new(
+            rng.gen_range(bounds.min.x..bounds.max.x),
+            rng.gen_range(bounds.min.y..bounds.max.y),
+        )
+    }
+
+    /// The found entities in a stable order, keeping duplicates.
+    fn sorted(entities: impl Iterator<Item = Entity>) -> Vec<Entity> {
+        let mut entities: Vec<_> = entities.collect();
+        entities.sort();
+        entities
+    }
+
+    /// The entities of `entries` that `filter` accepts.
+    fn scan(entries: &[(Entity, Rect)], filter: impl Fn(Rect) -> bool) -> Vec<Entity> {
+        sorted(
+            entries
+                .iter()
+                .filter(|(_, rect)| filter(*rect))
+                .map(|(entity, _)| *entity),
+        )
+    }
+
+    #[test]
+    fn query_point_finds_the_same_as_a_scan() {
+        let rng = &mut StdRng::seed_from_u64(0);
+        let (grid, entries) = random_grid(rng);
+        for _ in 0..1000 {
+            let point = random_point(rng);
+            assert_eq!(
+                sorted(grid.query_point(point)),
+                scan(&entries, |rect| rect.contains(point)),
+                "at {point}",
+            );
+        }
+    }
+
+    #[test]
+    fn query_rect_finds_the_same_as_a_scan() {
+        let rng = &mut StdRng::seed_from_u64(1);
+        let (grid, entries) = random_grid(rng);
+        for _ in 0..1000 {
+            let query = Rect::from_corners(random_point(rng), random_point(rng));
+            assert_eq!(
+                sorted(grid.query_rect(query)),
+                scan(&entries, |rect| overlaps(query, rect)),
+                "in {query:?}",
+            );
+        }
+    }
+
+    #[test]
+    fn query_circle_finds_the_same_as_a_scan() {
+        let rng = &mut StdRng::seed_from_u64(2);
+        let (grid, entries) = random_grid(rng);
+        for _ in 0..1000 {
+            let center = random_point(rng);
+            let radius = rng.gen_range(0.0..500.0);
+            assert_eq!(
+                sorted(grid.query_circle(center, radius)),
+                scan(&entries, |rect| {
+                    center.clamp(rect.min, rect.max).distance(center) <= radius
+                }),
+                "around {center} with radius {radius}",
+            );
+        }
+    }
+
+    #[test]
+    fn entries_in_many_cells_are_found_once() {
+        let mut grid = SpatialGrid::new(AREA);
+        let entity = Entity::from_raw(0);
+        grid.insert(entity, AREA.inflate(OUTSIDE));
+
+        assert_eq!(grid.query_point(Vec2::ZERO).collect::<Vec<_>>(), [entity]);
+        assert_eq!(grid.query_rect(AREA).collect::<Vec<_>>(), [entity]);
+        assert_eq!(
+            grid.query_circle(Vec2::ZERO, 1000.0).collect::<Vec<_>>(),
+            [entity]
+        );
+    }
+
+    #[test]
+    fn entries_outside_of_the_grid_are_found() {
+        let mut grid = SpatialGrid::new(AREA);
+        let entity = Entity::from_raw(0);
+        let far = Vec2::new(5000.0, -5000.0);
+        grid.insert(entity, Rect::from_center_size(far, Vec2::splat(10.0)));
+
+        assert_eq!(grid.query_point(far).collect::<Vec<_>>(), [entity]);
+        assert_eq!(
+            grid.query_rect(Rect::from_center_size(far, Vec2::ONE))
+                .collect::<Vec<_>>(),
+            [entity]
+        );
+        assert_eq!(grid.query_circle(far, 1.0).collect::<Vec<_>>(), [entity]);
+        // Points in the same border cell don't find it.
+        let border = Vec2::new(AREA.max.x, AREA.min.y) + Vec2::new(MARGIN, -MARGIN);
+        assert_eq!(grid.query_point(border).count(), 0);
+        assert_eq!(grid.query_circle(border, 100.0).count(), 0);
+    }
+}
@@ -0,0 +1,322 @@
+//! Statistics of the current run, for the score screen and for balancing
+//! levels from playtests.
+//!
+//! The totals of shots, hits and misses are kept in the
+//! [`GameScore`](crate::screens::GameScore). The [`RunStats`] break them down
+//! by creature and wave.
+//!
+//! Native builds can export them as JSON and CSV files to the data directory.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy::prelude::*;
+
+use crate::{
+    screens::{GameplayArea, Screen},
+    AppSet,
+};
+
+use super::{
+    creature::{Creature, DeathAnimation},
+    creature_image::CreatureImage,
+    events::{
+        BulletLanded, CreatureHit, GameOver, GameOverReason, ShotFired, WaveCleared, WaveStarted,
+    },
+    level::WaveTimer,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<RunStats>();
+    app.add_systems(OnEnter(Screen::Gameplay), reset_stats);
+    app.add_systems(
+        Update,
+        (record_waves, record_shots, record_landings)
+            .chain()
+            .after(AppSet::Update)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+#[derive(Resource, Debug, Clone, Default, serde::Serialize)]
+pub struct RunStats {
+    /// Bullets that hit and missed, by the kind of creature that was hit, or
+    /// the closest creature for misses. A bullet that hit several kinds of
+    /// creatures counts as a hit for each of them.
+    pub creatures: BTreeMap<CreatureImage, CreatureStats>,
+    pub waves: Vec<WaveStats>,
+    /// Where bullets were thrown, relative to the [`GameplayArea`]. `(0, 0)` is
+    /// the bottom left and `(1, 1)` the top right corner.
+    pub targets: Vec<Vec2>,
+    /// The wave that is being played.
+    #[serde(skip)]
+    current_wave: u32,
+}
+
+#[derive(Debug, Clone, Copy, Default, serde::Serialize)]
+pub struct CreatureStats {
+    pub hits: u32,
+    pub misses: u32,
+}
+
+impl CreatureStats {
+    /// The fraction of bullets that hit, between 0 and 1.
+    pub fn accuracy(&self) -> f32 {
+        accuracy(self.hits, self.misses)
+    }
+}
+
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+pub struct WaveStats {
+    pub wave: u32,
+    pub time_secs: f32,
+    /// Whether all creatures of the wave were killed.
+    pub cleared: bool,
+}
+
+/// The stats as they are exported, with the totals of the run.
+#[cfg(not(target_family = "wasm"))]
+#[derive(serde::Serialize)]
+struct RunExport<'a> {
+    shots: u32,
+    hits: u32,
+    misses: u32,
+    #[serde(flatten)]
+    stats: &'a RunStats,
+}
+
+impl RunStats {
+    /// Count the targets in a grid of `columns` x `rows` cells over the
+    /// gameplay area, row by row from the top.
+    pub fn heatmap(&self, columns: usize, rows: usize) -> Vec<u32> {
+        let mut cells = vec![0; columns * rows];
+        for target in &self.targets {
+            let column = ((target.x * columns as f32) as usize).min(columns - 1);
+            let row = (((1.0 - target.y) * rows as f32) as usize).min(rows - 1);
+            cells[row * columns + column] += 1;
+        }
+        cells
+    }
+
+    /// The stats and the totals of `game_score` as JSON.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn to_json(&self, game_score: &crate::screens::GameScore) -> String {
+        let export = RunExport {
+            shots: game_score.shots,
+            hits: game_score.hits,
+            misses: game_score.misses,
+            stats: self,
+        };
+        serde_json::to_string_pretty(&export).expect("run stats are always valid JSON")
+    }
+
+    /// The per-creature stats as CSV.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn creatures_csv(&self) -> String {
+        let mut csv = "creature,hits,misses,accuracy\n".to_string();
+        for (creature, stats) in &self.creatures {
+            csv += &format!(
+                "{creature:?},{},{},{:.3}\n",
+                stats.hits,
+                stats.misses,
+                stats.accuracy()
+            );
+        }
+        csv
+    }
+
+    /// The per-wave stats as CSV.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn waves_csv(&self) -> String {
+        let mut csv = "wave,time_secs,cleared\n".to_string();
+        for wave in &self.waves {
+            csv += &format!("{},{:.3},{}\n", wave.wave, wave.time_secs, wave.cleared);
+        }
+        csv
+    }
+
+    /// The bullet targets as CSV.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn targets_csv(&self) -> String {
+        let mut csv = "x,y\n".to_string();
+        for target in &self.targets {
+            csv += &format!("{:.4},{:.4}\n", target.x, target.y);
+        }
+        csv
+    }
+
+    /// Write the stats and the totals of `game_score` to a new directory in
+    /// the data directory and return its path.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn export(
+        &self,
+        game_score: &crate::screens::GameScore,
+    ) -> Result<std::path::PathBuf, String> {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_err(|err| err.to_string())?
+            .as_secs();
+        let dir = crate::save::data_dir()
+            .ok_or("no data directory")?
+            .join("stats")
+            .join(format!("run-{timestamp}"));
+        std::fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+        for (name, content) in [
+            ("run.json", self.to_json(game_score)),
+            ("creatures.csv", self.creatures_csv()),
+            ("waves.csv", self.waves_csv()),
+            ("targets.csv", self.targets_csv()),
+        ] {
+            std::fs::write(dir.join(name), content).map_err(|err| err.to_string())?;
+        }
+        Ok(dir)
+    }
+}
+
+/// The fraction of bullets that hit, between 0 and 1.
+pub fn accuracy(hits: u32, misses: u32) -> f32 {
+    if hits + misses == 0 {
+        0.0
+    } else {
+        hits as f32 / (hits + misses) as f32
+    }
+}
+
+fn reset_stats(mut stats: ResMut<RunStats>) {
+    *stats = RunStats::default();
+}
+
+fn record_waves(
+    mut started_events: EventReader<WaveStarted>,
+    mut cleared_events: EventReader<WaveCleared>,
+    mut game_over_events: EventReader<GameOver>,
+    timer: Option<Res<WaveTimer>>,
+    mut stats: ResMut<RunStats>,
+) {
+    for event in cleared_events.read() {
+        let wave = stats.current_wave;
+        stats.waves.push(WaveStats {
+            wave,
+            time_secs: event.time.as_secs_f32(),
+            cleared: true,
+        });
+    }
+    // A won game already cleared its last wave.
+    let lost = game_over_events
+        .read()
+        .any(|event| event.reason != GameOverReason::Won);
+    if lost {
+        let wave = stats.current_wave;
+        stats.waves.push(WaveStats {
+            wave,
+            time_secs: timer.map_or(0.0, |timer| timer.0.elapsed_secs()),
+            cleared: false,
+        });
+    }
+    if let Some(event) = started_events.read().last() {
+        stats.current_wave = event.wave;
+    }
+}
+
+fn record_shots(
+    mut events: EventReader<ShotFired>,
+    gameplay_area: Option<Res<GameplayArea>>,
+    mut stats: ResMut<RunStats>,
+) {
+    for event in events.read() {
+        if let Some(area) = &gameplay_area {
+            let area = area.main_area;
+            let target = ((event.target - area.min) / area.size()).clamp(Vec2::ZERO, Vec2::ONE);
+            stats.targets.push(target);
+        }
+    }
+}
+
+fn record_landings(
+    mut events: EventReader<BulletLanded>,
+    mut hit_events: EventReader<CreatureHit>,
+    creatures: Query<(&Transform, &CreatureImage), (With<Creature>, Without<DeathAnimation>)>,
+    mut stats: ResMut<RunStats>,
+) {
+    // The hits of each bullet are sent before it lands, in the same order.
+    let mut hits = hit_events.read();
+    for landing in events.read() {
+        if !landing.missed() {
+            let images = hits
+                .by_ref()
+                .take(landing.hits as usize)
+                .map(|hit| hit.image)
+                .collect::<BTreeSet<_>>();
+            for image in images {
+                stats.creatures.entry(image).or_default().hits += 1;
+            }
+            continue;
+        }
+        let closest = creatures.iter().min_by(|(a, _), (b, _)| {
+            let distance_a = a.translation.xy().distance_squared(landing.position);
+            let distance_b = b.translation.xy().distance_squared(landing.position);
+            distance_a.total_cmp(&distance_b)
+        });
+        if let Some((_, image)) = closest {
+            stats.creatures.entry(*image).or_default().misses += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heatmap_counts_targets_from_the_top_left() {
+        let stats = RunStats {
+            targets: vec![
+                Vec2::new(0.25, 0.75),
+                Vec2::new(1.0, 1.0),
+                Vec2::new(0.0, 0.0),
+                Vec2::new(0.0, 0.0),
+                Vec2::new(0.9, 0.1),
+            ],
+            ..default()
+        };
+        assert_eq!(stats.heatmap(2, 2), vec![1, 1, 2, 1]);
+        assert_eq!(stats.heatmap(1, 1), vec![5]);
+    }
+
+    #[test]
+    fn creatures_csv_has_a_row_per_creature() {
+        let mut stats = RunStats::default();
+        stats
+            .creatures
+            .insert(CreatureImage::Snake, CreatureStats { hits: 0, misses: 0 });
+        stats
+            .creatures
+            .insert(CreatureImage::Fox, CreatureStats { hits: 3, misses: 1 });
+        assert_eq!(
+            stats.creatures_csv(),
+            "creature,hits,misses,accuracy\nFox,3,1,0.750\nSnake,0,0,0.000\n"
+        );
+    }
+
+    #[test]
+    fn waves_csv_has_a_row_per_wave() {
+        let stats = RunStats {
+            waves: vec![
+                WaveStats {
+                    wave: 1,
+                    time_secs: 12.5,
+                    cleared: true,
+                },
+                WaveStats {
+                    wave: 2,
+                    time_secs: 3.25,
+                    cleared: false,
+                },
+            ],
+            ..default()
+        };
+        assert_eq!(
+            stats.waves_csv(),
+            "wave,time_secs,cleared\n1,12.500,true\n2,3.250,false\n"
+        );
+    }
+}
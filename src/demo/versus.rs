@@ -0,0 +1,112 @@
+//! Local versus mode for two players on one machine.
+//!
+//! The players take turns. In every round, both of them play the same
+//! generated wave, one after the other. Whoever has more points after
+//! [`VERSUS_ROUNDS`] rounds wins.
+
+use bevy::prelude::*;
+
+use crate::{
+    screens::{GameScore, Screen},
+    AppSet,
+};
+
+use super::{
+    events::{GameOver, GameOverReason},
+    level::GameMode,
+};
+
+/// Number of players taking turns.
+pub const VERSUS_PLAYERS: usize = 2;
+/// Number of waves each player plays.
+pub const VERSUS_ROUNDS: u32 = 5;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Versus>();
+    app.add_systems(
+        Update,
+        end_turn
+            .after(AppSet::Update)
+            .run_if(in_state(Screen::Gameplay).and_then(is_versus)),
+    );
+}
+
+/// The state of a versus game.
+#[derive(Resource, Debug, Clone)]
+pub struct Versus {
+    /// The round that is played, counted from 1.
+    pub round: u32,
+    /// The player whose turn it is, counted from 0.
+    pub player: usize,
+    /// The score of each player, summed up over all their turns.
+    pub scores: [GameScore; VERSUS_PLAYERS],
+}
+
+impl Default for Versus {
+    fn default() -> Self {
+        Self {
+            round: 1,
+            player: 0,
+            scores: default(),
+        }
+    }
+}
+
+impl Versus {
+    /// Whether all players played all rounds.
+    pub fn is_finished(&self) -> bool {
+        self.round > VERSUS_ROUNDS
+    }
+
+    /// The player with the most points, or `None` on a tie.
+    pub fn winner(&self) -> Option<usize> {
+        let best = self.scores.iter().map(|score| score.points).max()?;
+        let mut leaders = (0..VERSUS_PLAYERS).filter(|&player| self.scores[player].points == best);
+        let leader = leaders.next();
+        if leaders.next().is_some() {
+            None
+        } else {
+            leader
+        }
+    }
+
+    /// Add the score of a finished turn and move on to the next player.
+    fn record_turn(&mut self, turn: &GameScore) {
+        let total = &mut self.scores[self.player];
+        total.points += turn.points;
+        total.shots += turn.shots;
+        total.misses += turn.misses;
+        if turn.win {
+            total.score += 1;
+        }
+
+        self.player += 1;
+        if self.player == VERSUS_PLAYERS {
+            self.player = 0;
+            self.round += 1;
+        }
+    }
+}
+
+pub fn is_versus(mode: Res<GameMode>) -> bool {
+    matches!(*mode, GameMode::Versus { .. })
+}
+
+/// A turn is a single wave. Hand over to the next player when it's over.
+fn end_turn(
+    mut events: EventReader<GameOver>,
+    mut game_score: ResMut<GameScore>,
+    mut versus: ResMut<Versus>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    let Some(event) = events.read().last() else {
+        return;
+    };
+    game_score.win = event.reason == GameOverReason::Won;
+    versus.record_turn(&game_score);
+    if versus.is_finished() {
+        next_screen.set(Screen::Score);
+    } else {
+        next_screen.set(Screen::Handoff);
+    }
+}
@@ -0,0 +1,466 @@
+//! An in-game console to run commands while playing, e.g. to skip to a late
+//! wave. Open it with [`TOGGLE_KEY`] and type `help` to list the commands.
+//!
+//! Other modules can add their own commands with
+//! [`AddConsoleCommand::add_console_command`].
+
+use std::{collections::BTreeMap, str::FromStr, time::Duration};
+
+use bevy::{
+    ecs::{system::SystemId, world::Command},
+    input::{
+        common_conditions::input_just_pressed,
+        keyboard::{Key, KeyboardInput},
+        InputSystem,
+    },
+    prelude::*,
+};
+
+use crate::{
+    demo::{
+        creature::{Creature, KillCreature, SpawnCreature},
+        creature_image::CreatureImage,
+        daily::DAILY_WAVES,
+        level::{ActiveLevel, GameMode, Levels, WaveCounter, WaveTimer},
+        movement_pattern::MovementPatternDefinition,
+        powerup::{self, ActivePowerUps, PowerUp, PowerUpKind, PowerUpSpawner},
+    },
+    pool::Release,
+    screens::{GameScore, Screen},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Console>();
+    app.init_resource::<ConsoleCommands>();
+    app.init_resource::<GodMode>();
+    app.add_systems(
+        PreUpdate,
+        block_game_input
+            .after(InputSystem)
+            .run_if(|console: Res<Console>| console.open),
+    );
+    app.add_systems(
+        Update,
+        (
+            toggle_console.run_if(input_just_pressed(TOGGLE_KEY)),
+            read_console_input.run_if(|console: Res<Console>| console.open),
+            update_console_text.run_if(resource_changed::<Console>),
+            apply_god_mode.run_if(resource_exists::<WaveTimer>),
+        )
+            .chain(),
+    );
+
+    app.add_console_command("help", "help", help);
+    app.add_console_command("wave", "wave <n>", skip_to_wave);
+    app.add_console_command("spawn", "spawn <creature> <x> <y>", spawn);
+    app.add_console_command("kill_all", "kill_all", kill_all);
+    app.add_console_command("timescale", "timescale <speed>", set_timescale);
+    app.add_console_command("god", "god", toggle_god_mode);
+    app.add_console_command("screen", "screen <screen>", set_screen);
+    app.add_console_command("give", "give <powerup>", give_power_up);
+}
+
+const TOGGLE_KEY: KeyCode = KeyCode::F1;
+
+/// Number of output lines shown above the input.
+const SHOWN_LINES: usize = 8;
+
+/// The system behind a console command. It gets the arguments after the name
+/// of the command and returns the text to print.
+pub type ConsoleCommandSystem = SystemId<Vec<String>, Result<String, String>>;
+
+/// All commands the console knows, by name.
+#[derive(Resource, Default)]
+pub struct ConsoleCommands(BTreeMap<&'static str, (&'static str, ConsoleCommandSystem)>);
+
+pub trait AddConsoleCommand {
+    /// Register `system` to run when `name` is entered in the console. `usage`
+    /// is shown by the `help` command.
+    fn add_console_command<M>(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        system: impl IntoSystem<Vec<String>, Result<String, String>, M> + 'static,
+    ) -> &mut Self;
+}
+
+impl AddConsoleCommand for App {
+    fn add_console_command<M>(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        system: impl IntoSystem<Vec<String>, Result<String, String>, M> + 'static,
+    ) -> &mut Self {
+        let id = self.world_mut().register_system(system);
+        self.world_mut()
+            .get_resource_or_insert_with(ConsoleCommands::default)
+            .0
+            .insert(name, (usage, id));
+        self
+    }
+}
+
+#[derive(Resource, Default)]
+struct Console {
+    open: bool,
+    input: String,
+    /// Entered commands and their output.
+    lines: Vec<String>,
+}
+
+#[derive(Component)]
+struct ConsoleText;
+
+fn toggle_console(mut commands: Commands, mut console: ResMut<Console>) {
+    console.open = !console.open;
+    if !console.open {
+        return;
+    }
+    commands
+        .spawn((
+            Name::new("Console"),
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(0.0),
+                    width: Val::Percent(100.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.8).into(),
+                z_index: ZIndex::Global(100),
+                ..default()
+            },
+        ))
+        .with_children(|children| {
+            children.spawn((
+                Name::new("Console Text"),
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 18.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                ConsoleText,
+            ));
+        });
+}
+
+/// Keys typed into the console shouldn't also pause the game, throw balls
+/// and so on.
+fn block_game_input(mut keys: ResMut<ButtonInput<KeyCode>>) {
+    let blocked = keys
+        .get_pressed()
+        .chain(keys.get_just_released())
+        .filter(|&&key| key != TOGGLE_KEY)
+        .copied()
+        .collect::<Vec<_>>();
+    for key in blocked {
+        keys.reset(key);
+    }
+}
+
+fn read_console_input(
+    mut commands: Commands,
+    mut console: ResMut<Console>,
+    mut events: EventReader<KeyboardInput>,
+) {
+    for event in events.read() {
+        if !event.state.is_pressed() {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Enter => {
+                let line = std::mem::take(&mut console.input);
+                if !line.trim().is_empty() {
+                    console.lines.push(format!("> {line}"));
+                    commands.add(RunConsoleCommand(line));
+                }
+            }
+            Key::Backspace => {
+                console.input.pop();
+            }
+            Key::Space => console.input.push(' '),
+            Key::Character(text) => console.input.push_str(text),
+            _ => (),
+        }
+    }
+}
+
+fn update_console_text(
+    mut commands: Commands,
+    console: Res<Console>,
+    mut text_query: Query<(&mut Text, &Parent), With<ConsoleText>>,
+) {
+    for (mut text, parent) in &mut text_query {
+        if !console.open {
+            commands.entity(parent.get()).despawn_recursive();
+            continue;
+        }
+        let first = console.lines.len().saturating_sub(SHOWN_LINES);
+        let mut value = console.lines[first..].join("\n");
+        if !value.is_empty() {
+            value.push('\n');
+        }
+        value.push_str(&format!("> {}_", console.input));
+        text.sections[0].value = value;
+    }
+}
+
+/// A command to run a line entered into the console.
+struct RunConsoleCommand(String);
+
+impl Command for RunConsoleCommand {
+    fn apply(self, world: &mut World) {
+        let mut words = self.0.split_whitespace().map(str::to_string);
+        let Some(name) = words.next() else {
+            return;
+        };
+        let args = words.collect::<Vec<_>>();
+
+        let system = world
+            .resource::<ConsoleCommands>()
+            .0
+            .get(name.as_str())
+            .copied();
+        let output = match system {
+            Some((_, id)) => world
+                .run_system_with_input(id, args)
+                .unwrap_or_else(|err| Err(err.to_string())),
+            None => Err(format!("Unknown command '{name}', try 'help'")),
+        };
+        // Runs that were changed from the console don't count for high
+        // scores and achievements.
+        if output.is_ok() && *world.resource::<State<Screen>>().get() == Screen::Gameplay {
+            world.resource_mut::<GameScore>().console_used = true;
+        }
+        let line = output.unwrap_or_else(|err| format!("Error: {err}"));
+        if !line.is_empty() {
+            world.resource_mut::<Console>().lines.push(line);
+        }
+    }
+}
+
+/// Parse the argument at `index`.
+fn arg<T: FromStr>(args: &[String], index: usize, name: &str) -> Result<T, String> {
+    let value = args
+        .get(index)
+        .ok_or_else(|| format!("missing argument <{name}>"))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid <{name}>: '{value}'"))
+}
+
+/// Find the value whose name matches the argument at `index`, ignoring case.
+fn named_arg<T: Clone + std::fmt::Debug>(
+    args: &[String],
+    index: usize,
+    name: &str,
+    values: &[T],
+) -> Result<T, String> {
+    let value = arg::<String>(args, index, name)?;
+    values
+        .iter()
+        .find(|candidate| format!("{candidate:?}").eq_ignore_ascii_case(&value))
+        .ok_or_else(|| {
+            let names = values
+                .iter()
+                .map(|value| format!("{value:?}"))
+                .collect::<Vec<_>>();
+            format!(
+                "unknown <{name}> '{value}', expected one of {}",
+                names.join(", ")
+            )
+        })
+        .cloned()
+}
+
+fn in_game(screen: &State<Screen>) -> Result<(), String> {
+    if *screen.get() == Screen::Gameplay {
+        Ok(())
+    } else {
+        Err("only available during a game".to_string())
+    }
+}
+
+fn help(In(_args): In<Vec<String>>, commands: Res<ConsoleCommands>) -> Result<String, String> {
+    let usages = commands.0.values().map(|(usage, _)| *usage);
+    Ok(usages.collect::<Vec<_>>().join(", "))
+}
+
+/// Skip to a wave. The current wave is removed without being cleared and the
+/// new one is spawned in the next frame, like the first wave of a run.
+fn skip_to_wave(
+    In(args): In<Vec<String>>,
+    mut commands: Commands,
+    wave_counter: Option<ResMut<WaveCounter>>,
+    mode: Res<GameMode>,
+    levels: Option<Res<Levels>>,
+    creatures: Query<Entity, With<Creature>>,
+    power_ups: Query<Entity, Or<(With<PowerUp>, With<PowerUpSpawner>)>>,
+) -> Result<String, String> {
+    let wave: u32 = arg(&args, 0, "n")?;
+    let Some(mut wave_counter) = wave_counter else {
+        return Err("only available during a game".to_string());
+    };
+    if wave == 0 {
+        return Err("waves start at 1".to_string());
+    }
+    let last_wave = match *mode {
+        GameMode::Campaign => levels.map_or(0, |levels| levels.count()),
+        GameMode::Daily { .. } => DAILY_WAVES,
+        GameMode::Endless { .. } | GameMode::Party { .. } => u32::MAX,
+        GameMode::Versus { .. } => return Err("a versus turn is a single wave".to_string()),
+    };
+    if wave > last_wave {
+        return Err(format!("there are only {last_wave} waves"));
+    }
+    wave_counter.wave = wave - 1;
+    for entity in &creatures {
+        commands.add(Release::<Creature>::new(entity));
+    }
+    for entity in &power_ups {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.remove_resource::<ActiveLevel>();
+    Ok(format!("Skipping to wave {wave}"))
+}
+
+fn spawn(
+    In(args): In<Vec<String>>,
+    mut commands: Commands,
+    screen: Res<State<Screen>>,
+) -> Result<String, String> {
+    in_game(&screen)?;
+    let image = named_arg(&args, 0, "creature", &CreatureImage::ALL)?;
+    let x: f32 = arg(&args, 1, "x")?;
+    let y: f32 = arg(&args, 2, "y")?;
+    commands.add(SpawnCreature {
+        image,
+        max_speed: 400.0,
+        pos: Vec2::new(x, y),
+        movement: MovementPatternDefinition::Constant {
+            speed: Vec2::new(1.0, 0.0),
+        }
+        .build(),
+        shrink_duration: Duration::from_secs(10),
+        wrap: false,
+        scale: 1.0,
+        mass: 1.0,
+        radius: None,
+        on_death: Vec::new(),
+    });
+    Ok(format!("Spawned {image:?} at ({x}, {y})"))
+}
+
+fn kill_all(
+    In(_args): In<Vec<String>>,
+    mut commands: Commands,
+    creatures: Query<Entity, With<Creature>>,
+) -> Result<String, String> {
+    for entity in &creatures {
+        commands.add(KillCreature {
+            entity,
+            player: None,
+        });
+    }
+    Ok(format!("Killed {} creatures", creatures.iter().len()))
+}
+
+fn set_timescale(
+    In(args): In<Vec<String>>,
+    mut time: ResMut<Time<Virtual>>,
+) -> Result<String, String> {
+    let speed: f32 = arg(&args, 0, "speed")?;
+    if speed.is_nan() || speed < 0.0 {
+        return Err("the speed can't be negative".to_string());
+    }
+    time.set_relative_speed(speed);
+    Ok(format!("Time runs at {speed}x"))
+}
+
+/// While enabled, the wave timer is paused, so waves can't be lost.
+#[derive(Resource, Default)]
+struct GodMode(bool);
+
+fn toggle_god_mode(
+    In(_args): In<Vec<String>>,
+    mut god_mode: ResMut<GodMode>,
+    wave_timer: Option<ResMut<WaveTimer>>,
+) -> Result<String, String> {
+    god_mode.0 = !god_mode.0;
+    if let (false, Some(mut timer)) = (god_mode.0, wave_timer) {
+        timer.0.unpause();
+    }
+    Ok(format!(
+        "God mode {}",
+        if god_mode.0 { "on" } else { "off" }
+    ))
+}
+
+fn apply_god_mode(
+    god_mode: Res<GodMode>,
+    mut wave_timer: ResMut<WaveTimer>,
+    mut game_score: ResMut<GameScore>,
+) {
+    // God mode stays on in the next runs.
+    if god_mode.0 && !game_score.console_used {
+        game_score.console_used = true;
+    }
+    if god_mode.0 && !wave_timer.0.paused() {
+        wave_timer.0.pause();
+    }
+}
+
+fn set_screen(
+    In(args): In<Vec<String>>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) -> Result<String, String> {
+    let screen = named_arg(
+        &args,
+        0,
+        "screen",
+        &[
+            Screen::Splash,
+            Screen::Loading,
+            Screen::Title,
+            Screen::Credits,
+            Screen::Settings,
+            Screen::Achievements,
+            Screen::LevelSelect,
+            Screen::Gameplay,
+            Screen::Handoff,
+            Screen::Party,
+            Screen::Score,
+            Screen::Dev,
+            Screen::Editor,
+        ],
+    )?;
+    next_screen.set(screen.clone());
+    Ok(format!("Switching to {screen:?}"))
+}
+
+fn give_power_up(
+    In(args): In<Vec<String>>,
+    screen: Res<State<Screen>>,
+    mut active: ResMut<ActivePowerUps>,
+    mut time: ResMut<Time<Virtual>>,
+    mut wave_timer: Option<ResMut<WaveTimer>>,
+) -> Result<String, String> {
+    in_game(&screen)?;
+    let kind = named_arg(
+        &args,
+        0,
+        "powerup",
+        &[
+            PowerUpKind::SlowTime,
+            PowerUpKind::Freeze,
+            PowerUpKind::MultiBall,
+            PowerUpKind::ExtraTime,
+        ],
+    )?;
+    powerup::activate(kind, &mut active, &mut time, wave_timer.as_deref_mut());
+    Ok(format!("Activated {kind:?}"))
+}
@@ -0,0 +1,135 @@
+//! Development tools for the game. This plugin is only enabled in dev builds.
+
+mod console;
+
+use std::time::Duration;
+
+use bevy::{
+    dev_tools::{
+        states::log_transitions,
+        ui_debug_overlay::{DebugUiPlugin, UiDebugOptions},
+    },
+    input::common_conditions::input_just_pressed,
+    prelude::*,
+};
+
+use crate::{
+    demo::{
+        creature::{hitbox, Bullet, Creature, CreatureScale},
+        creature_image::CreatureImage,
+        difficulty::Difficulty,
+        movement::{
+            draw_path, predict_path, EdgeBehavior, MovementController, MovementFrozen, ScreenWrap,
+        },
+        movement_pattern::MovementPattern,
+    },
+    screens::{GameplayArea, Screen},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(console::plugin);
+
+    // Log `Screen` state transitions.
+    app.add_systems(Update, log_transitions::<Screen>);
+
+    // Toggle the debug overlay for UI.
+    app.add_plugins(DebugUiPlugin);
+    app.add_systems(
+        Update,
+        toggle_debug_ui.run_if(input_just_pressed(TOGGLE_KEY)),
+    );
+
+    // Toggle the overlay that shows where creatures and bullets are going.
+    app.init_resource::<MovementDebugOptions>();
+    app.add_systems(
+        Update,
+        (
+            toggle_movement_debug.run_if(input_just_pressed(MOVEMENT_TOGGLE_KEY)),
+            draw_movement_debug.run_if(
+                in_state(Screen::Gameplay)
+                    .and_then(|options: Res<MovementDebugOptions>| options.enabled),
+            ),
+        ),
+    );
+}
+
+const TOGGLE_KEY: KeyCode = KeyCode::Backquote;
+
+const MOVEMENT_TOGGLE_KEY: KeyCode = KeyCode::F2;
+
+/// How far ahead the paths of creatures are predicted.
+const PREDICTION_DURATION: Duration = Duration::from_secs(3);
+const PREDICTION_STEP: Duration = Duration::from_millis(16);
+
+fn toggle_debug_ui(mut options: ResMut<UiDebugOptions>) {
+    options.toggle();
+}
+
+#[derive(Resource, Default)]
+struct MovementDebugOptions {
+    enabled: bool,
+}
+
+fn toggle_movement_debug(mut options: ResMut<MovementDebugOptions>) {
+    options.enabled = !options.enabled;
+}
+
+fn draw_movement_debug(
+    mut gizmos: Gizmos,
+    gameplay_area: Res<GameplayArea>,
+    difficulty: Res<Difficulty>,
+    creatures: Query<
+        (
+            &Transform,
+            &MovementController,
+            &MovementPattern,
+            &CreatureImage,
+            &CreatureScale,
+            Has<ScreenWrap>,
+        ),
+        Without<MovementFrozen>,
+    >,
+    hitboxes: Query<(&Transform, &CreatureImage), With<Creature>>,
+    bullets: Query<&Bullet>,
+) {
+    let area = gameplay_area.main_area;
+    gizmos.rect_2d(area.center(), 0.0, area.size(), Color::WHITE);
+
+    for (transform, controller, pattern, image, scale, wrap) in &creatures {
+        let edge = if wrap {
+            EdgeBehavior::Wrap
+        } else {
+            EdgeBehavior::Bounce {
+                half_size: image.size().as_vec2() * scale.0 / 2.0,
+            }
+        };
+        let path = predict_path(
+            pattern.clone(),
+            controller.clone(),
+            transform.translation.xy(),
+            edge,
+            area,
+            PREDICTION_DURATION,
+            PREDICTION_STEP,
+        );
+        draw_path(&mut gizmos, &path, Color::srgb(0.2, 0.4, 1.0));
+    }
+
+    // Frozen creatures have a hitbox, too.
+    for (transform, image) in &hitboxes {
+        let hitbox = hitbox(image, transform, &difficulty);
+        gizmos.rect_2d(
+            hitbox.center(),
+            0.0,
+            hitbox.size(),
+            Color::srgb(1.0, 0.2, 0.2),
+        );
+    }
+
+    for bullet in &bullets {
+        if bullet.timer.finished() {
+            continue;
+        }
+        gizmos.circle_2d(bullet.target, 12.0, Color::srgb(1.0, 0.8, 0.0));
+    }
+}
@@ -1,66 +1,65 @@
 mod asset_tracking;
 pub mod audio;
+mod clipboard;
+pub mod config;
 mod demo;
 #[cfg(feature = "dev")]
 mod dev_tools;
+mod playfield;
+pub mod pool;
+mod save;
 mod screens;
 mod theme;
 
+use std::time::Duration;
+
 use bevy::{
+    app::ScheduleRunnerPlugin,
     asset::AssetMetaCheck,
     audio::{AudioPlugin, Volume},
     prelude::*,
-    window::WindowResolution,
+    render::{settings::WgpuSettings, RenderPlugin},
+    window::{ExitCondition, WindowMode, WindowResolution},
+    winit::WinitPlugin,
 };
+use config::AppConfig;
+
+pub use demo::{bench, spatial};
 
+/// The game. Uses the [`AppConfig`] resource if it was inserted before adding
+/// the plugin, and the default config otherwise.
 pub struct AppPlugin;
 
 impl Plugin for AppPlugin {
     fn build(&self, app: &mut App) {
+        let config = app
+            .world()
+            .get_resource::<AppConfig>()
+            .cloned()
+            .unwrap_or_default();
+        app.insert_resource(config.clone());
+
         // Order new `AppStep` variants by adding them here:
         app.configure_sets(
             Update,
-            (AppSet::TickTimers, AppSet::RecordInput, AppSet::Update).chain(),
+            (AppSet::TickTimers, AppSet::RecordInput, AppSet::Update)
+                .chain()
+                .run_if(not(screens::is_paused)),
         );
 
         // Spawn the main camera.
         app.add_systems(Startup, spawn_camera);
 
         // Add Bevy plugins.
-        app.add_plugins(
-            DefaultPlugins
-                .set(AssetPlugin {
-                    // Wasm builds will check for meta files (that don't exist) if this isn't set.
-                    // This causes errors and even panics on web build on itch.
-                    // See https://github.com/bevyengine/bevy_github_ci_template/issues/48.
-                    meta_check: AssetMetaCheck::Never,
-                    ..default()
-                })
-                .set(WindowPlugin {
-                    primary_window: Window {
-                        title: "Animal Arcade".to_string(),
-                        canvas: Some("#bevy".to_string()),
-                        fit_canvas_to_parent: false,
-                        prevent_default_event_handling: true,
-                        resolution: WindowResolution::new(800.0, 640.0),
-                        resizable: true,
-                        ..default()
-                    }
-                    .into(),
-                    ..default()
-                })
-                .set(AudioPlugin {
-                    global_volume: GlobalVolume {
-                        volume: Volume::new(0.3),
-                    },
-                    ..default()
-                }),
-        );
+        add_bevy_plugins(app, &config);
 
         // Add other plugins.
         app.add_plugins((
             asset_tracking::plugin,
+            audio::plugin,
             demo::plugin,
+            playfield::plugin,
+            save::plugin,
             screens::plugin,
             theme::plugin,
         ));
@@ -71,6 +70,69 @@ impl Plugin for AppPlugin {
     }
 }
 
+/// Add the Bevy plugins, set up for a window or, with
+/// [`AppConfig::headless_bench`], for running without one.
+pub(crate) fn add_bevy_plugins(app: &mut App, config: &AppConfig) {
+    let mode = if config.fullscreen && !config.windowed_dev {
+        WindowMode::BorderlessFullscreen
+    } else {
+        WindowMode::Windowed
+    };
+    let volume = if config.mute { 0.0 } else { 0.3 };
+    let plugins = DefaultPlugins
+        .set(AssetPlugin {
+            // Wasm builds will check for meta files (that don't exist) if this isn't set.
+            // This causes errors and even panics on web build on itch.
+            // See https://github.com/bevyengine/bevy_github_ci_template/issues/48.
+            meta_check: AssetMetaCheck::Never,
+            ..default()
+        })
+        .set(WindowPlugin {
+            primary_window: Window {
+                title: "Animal Arcade".to_string(),
+                canvas: Some("#bevy".to_string()),
+                // Fill the page on the web, so the game works on phones.
+                fit_canvas_to_parent: true,
+                prevent_default_event_handling: true,
+                resolution: WindowResolution::new(config.window_size.x, config.window_size.y),
+                mode,
+                resizable: true,
+                ..default()
+            }
+            .into(),
+            ..default()
+        })
+        .set(AudioPlugin {
+            global_volume: GlobalVolume {
+                volume: Volume::new(volume),
+            },
+            ..default()
+        });
+    if config.headless_bench {
+        // Run the game loop as fast as possible, without a window or GPU.
+        app.add_plugins((
+            plugins
+                .set(WindowPlugin {
+                    primary_window: None,
+                    exit_condition: ExitCondition::DontExit,
+                    ..default()
+                })
+                .set(RenderPlugin {
+                    render_creation: WgpuSettings {
+                        backends: None,
+                        ..default()
+                    }
+                    .into(),
+                    ..default()
+                })
+                .disable::<WinitPlugin>(),
+            ScheduleRunnerPlugin::run_loop(Duration::ZERO),
+        ));
+    } else {
+        app.add_plugins(plugins);
+    }
+}
+
 /// High-level groupings of systems for the app in the `Update` schedule.
 /// When adding a new variant, make sure to order it in the `configure_sets`
 /// call above.
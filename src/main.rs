@@ -2,8 +2,15 @@
 #![cfg_attr(not(feature = "dev"), windows_subsystem = "windows")]
 
 use bevy::prelude::*;
-use ludum_dare56::AppPlugin;
+use ludum_dare56::{bench, config::AppConfig, AppPlugin};
 
 fn main() -> AppExit {
-    App::new().add_plugins(AppPlugin).run()
+    let config = AppConfig::from_args();
+    if config.headless_bench {
+        return bench::run(&config);
+    }
+    App::new()
+        .insert_resource(config)
+        .add_plugins(AppPlugin)
+        .run()
 }
@@ -0,0 +1,174 @@
+//! A playfield of a fixed logical size that is scaled to the window.
+//!
+//! The world is always at least [`PLAYFIELD_SIZE`] units large, regardless of
+//! the size of the window. How the playfield is fitted into the window is
+//! chosen with [`PlayfieldScaling`]. The UI is scaled along with it.
+
+use bevy::{
+    prelude::*,
+    render::camera::{ScalingMode, Viewport},
+    window::{PrimaryWindow, WindowResized},
+};
+
+use crate::config::AppConfig;
+
+/// The size of the playfield in world units.
+pub const PLAYFIELD_SIZE: Vec2 = Vec2::new(800.0, 640.0);
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(PlayfieldScaling, VisibleArea)>();
+    let scaling = app.world().resource::<AppConfig>().scaling;
+    app.insert_resource(scaling);
+    app.insert_resource(VisibleArea(Rect::from_center_size(
+        Vec2::ZERO,
+        PLAYFIELD_SIZE,
+    )));
+    app.add_systems(
+        Update,
+        (
+            update_camera.run_if(
+                on_event::<WindowResized>()
+                    .or_else(resource_changed::<PlayfieldScaling>)
+                    .or_else(any_added_cameras),
+            ),
+            fill_screen,
+        )
+            .chain(),
+    );
+}
+
+/// How the playfield is fitted into the window.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+#[reflect(Resource)]
+pub enum PlayfieldScaling {
+    /// Keep the aspect ratio and show bars at the sides that don't fit.
+    #[default]
+    Letterbox,
+    /// Stretch the playfield to fill the window.
+    Fit,
+    /// Keep the aspect ratio and extend the playfield to fill the window.
+    Expand,
+}
+
+impl PlayfieldScaling {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "letterbox" => Some(Self::Letterbox),
+            "fit" => Some(Self::Fit),
+            "expand" => Some(Self::Expand),
+            _ => None,
+        }
+    }
+}
+
+/// The part of the world that is visible, in world units.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct VisibleArea(pub Rect);
+
+/// Sprites that are the size of the playfield and are scaled to cover the
+/// whole [`VisibleArea`], like backgrounds.
+#[derive(Component, Debug, Clone, Copy, Default, Reflect)]
+#[reflect(Component)]
+pub struct FillScreen;
+
+fn any_added_cameras(cameras: Query<(), Added<Camera>>) -> bool {
+    !cameras.is_empty()
+}
+
+fn update_camera(
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut camera_query: Query<(&mut Camera, &mut OrthographicProjection)>,
+    scaling: Res<PlayfieldScaling>,
+    mut ui_scale: ResMut<UiScale>,
+    mut visible_area: ResMut<VisibleArea>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        // Headless, nothing to scale.
+        return;
+    };
+    let Ok((mut camera, mut projection)) = camera_query.get_single_mut() else {
+        return;
+    };
+
+    let window_size = window.size();
+    let scale = (window_size / PLAYFIELD_SIZE).min_element();
+    if scale <= 0.0 {
+        // Minimized.
+        return;
+    }
+    ui_scale.0 = scale;
+
+    let mut visible_size = PLAYFIELD_SIZE;
+    match *scaling {
+        PlayfieldScaling::Letterbox => {
+            let physical_window_size = window.physical_size();
+            let physical_size = (PLAYFIELD_SIZE * scale * window.scale_factor())
+                .as_uvec2()
+                .clamp(UVec2::ONE, physical_window_size.max(UVec2::ONE));
+            camera.viewport = Some(Viewport {
+                physical_position: (physical_window_size - physical_size) / 2,
+                physical_size,
+                ..default()
+            });
+            projection.scaling_mode = ScalingMode::Fixed {
+                width: PLAYFIELD_SIZE.x,
+                height: PLAYFIELD_SIZE.y,
+            };
+        }
+        PlayfieldScaling::Fit => {
+            camera.viewport = None;
+            projection.scaling_mode = ScalingMode::Fixed {
+                width: PLAYFIELD_SIZE.x,
+                height: PLAYFIELD_SIZE.y,
+            };
+        }
+        PlayfieldScaling::Expand => {
+            camera.viewport = None;
+            projection.scaling_mode = ScalingMode::AutoMin {
+                min_width: PLAYFIELD_SIZE.x,
+                min_height: PLAYFIELD_SIZE.y,
+            };
+            visible_size = window_size / scale;
+        }
+    }
+
+    let area = Rect::from_center_size(Vec2::ZERO, visible_size);
+    if visible_area.0 != area {
+        visible_area.0 = area;
+    }
+}
+
+fn fill_screen(
+    visible_area: Res<VisibleArea>,
+    mut query: Query<(&mut Transform, Ref<FillScreen>)>,
+) {
+    let scale = (visible_area.0.size() / PLAYFIELD_SIZE).max_element();
+    for (mut transform, fill) in &mut query {
+        if visible_area.is_changed() || fill.is_added() {
+            transform.scale = Vec3::new(scale, scale, 1.0);
+        }
+    }
+}
+
+/// Convert a position in the window, like the cursor, to world coordinates.
+pub fn window_to_world(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    position: Vec2,
+) -> Option<Vec2> {
+    // The position is relative to the window, but the camera expects it
+    // relative to its viewport.
+    let offset = camera
+        .logical_viewport_rect()
+        .map_or(Vec2::ZERO, |viewport| viewport.min);
+    camera.viewport_to_world_2d(camera_transform, position - offset)
+}
+
+/// Convert a position in the window, like the cursor, to UI coordinates.
+pub fn window_to_ui(camera: &Camera, ui_scale: &UiScale, position: Vec2) -> Vec2 {
+    let offset = camera
+        .logical_viewport_rect()
+        .map_or(Vec2::ZERO, |viewport| viewport.min);
+    (position - offset) / ui_scale.0
+}
@@ -0,0 +1,125 @@
+//! Recycling of short-lived entities, like bullets, creatures, particles and
+//! sound effects.
+//!
+//! Spawning and despawning thousands of entities is slow. Instead, entities
+//! of a pooled kind are released into a [`Pool`] when they are done, and
+//! reused the next time an entity of that kind is spawned.
+//!
+//! A kind is identified by a marker component `T` that all of its entities
+//! have. Releasing an entity removes `T`, so queries for `T` don't see pooled
+//! entities, hides it, and runs the reset hook of the pool. The hook removes
+//! or resets the other components that would still do something while the
+//! entity waits in the pool, like movement.
+
+use std::marker::PhantomData;
+
+use bevy::{
+    ecs::{
+        entity::Entities,
+        system::EntityCommands,
+        world::{Command, EntityWorldMut},
+    },
+    prelude::*,
+};
+
+pub trait PoolApp {
+    /// Keep released entities with the component `T` around for reuse.
+    /// `reset` is called on every released entity.
+    fn init_pool<T: Component>(&mut self, reset: fn(&mut EntityWorldMut)) -> &mut Self;
+}
+
+impl PoolApp for App {
+    fn init_pool<T: Component>(&mut self, reset: fn(&mut EntityWorldMut)) -> &mut Self {
+        self.insert_resource(Pool::<T> {
+            free: Vec::new(),
+            reset,
+            spawned: 0,
+            reused: 0,
+            _marker: PhantomData,
+        })
+    }
+}
+
+/// Released entities of the kind `T`.
+#[derive(Resource)]
+pub struct Pool<T: Component> {
+    free: Vec<Entity>,
+    reset: fn(&mut EntityWorldMut),
+    /// Number of entities that had to be spawned.
+    pub spawned: u64,
+    /// Number of entities that were taken from the pool instead.
+    pub reused: u64,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Component> Pool<T> {
+    /// Take an entity from the pool, or spawn a new one if the pool is empty.
+    /// Insert all components of the kind into it, including `T`. A reused
+    /// entity is still hidden, so make sure to insert a visible
+    /// [`Visibility`], e.g. with a [`SpriteBundle`].
+    pub fn spawn<'a>(
+        &mut self,
+        commands: &'a mut Commands,
+        entities: &Entities,
+    ) -> EntityCommands<'a> {
+        // Pooled entities are still despawned with the rest of their screen,
+        // so skip the ones that are gone.
+        while let Some(entity) = self.free.pop() {
+            if entities.contains(entity) {
+                self.reused += 1;
+                return commands.entity(entity);
+            }
+        }
+        self.spawned += 1;
+        commands.spawn_empty()
+    }
+
+    /// Number of entities waiting in the pool.
+    pub fn len(&self) -> usize {
+        self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.free.is_empty()
+    }
+}
+
+/// Put an entity of the kind `T` back into its pool, instead of despawning
+/// it.
+pub struct Release<T: Component> {
+    entity: Entity,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Component> Release<T> {
+    pub fn new(entity: Entity) -> Self {
+        Self {
+            entity,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Component> Command for Release<T> {
+    fn apply(self, world: &mut World) {
+        let Some(reset) = world.get_resource::<Pool<T>>().map(|pool| pool.reset) else {
+            // Not pooled after all.
+            if let Some(entity) = world.get_entity_mut(self.entity) {
+                entity.despawn_recursive();
+            }
+            return;
+        };
+        let Some(mut entity) = world.get_entity_mut(self.entity) else {
+            return;
+        };
+        if entity.take::<T>().is_none() {
+            // Released twice in the same frame.
+            return;
+        }
+        if let Some(mut visibility) = entity.get_mut::<Visibility>() {
+            *visibility = Visibility::Hidden;
+        }
+        reset(&mut entity);
+        world.resource_mut::<Pool<T>>().free.push(self.entity);
+    }
+}
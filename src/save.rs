@@ -0,0 +1,229 @@
+//! Data that is kept between runs of the game, like high scores.
+//!
+//! The data is stored as RON, in a file in the user's data directory, or in
+//! the local storage of the browser in web builds.
+
+use std::collections::{BTreeMap, BTreeSet};
+#[cfg(not(target_family = "wasm"))]
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+
+use crate::demo::{
+    difficulty::{Difficulty, DifficultyModifiers},
+    juice::EffectSettings,
+};
+
+/// Endless seeds whose high score is kept, the ones with the best scores.
+const MAX_ENDLESS_SEEDS: usize = 10;
+/// The key of the save data in the local storage of the browser.
+#[cfg(target_family = "wasm")]
+const STORAGE_KEY: &str = "animal-arcade-save";
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(SaveData::load());
+}
+
+/// The directory the game keeps its files in.
+#[cfg(not(target_family = "wasm"))]
+pub fn data_dir() -> Option<PathBuf> {
+    let data_dir = std::env::var_os("XDG_DATA_HOME")
+        .or_else(|| std::env::var_os("APPDATA"))
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share"))
+        })?;
+    Some(data_dir.join("animal-arcade"))
+}
+
+#[derive(Resource, Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SaveData {
+    /// The difficulty that was selected last.
+    pub difficulty: Difficulty,
+    /// The modifiers of the custom difficulty, kept while another
+    /// difficulty is selected.
+    pub custom_difficulty: DifficultyModifiers,
+    /// High scores, by the name of the difficulty they were reached at.
+    pub high_scores: BTreeMap<String, HighScores>,
+    /// Results of the daily challenges, by day.
+    pub daily: BTreeMap<u32, DailyResult>,
+    /// The ids of the unlocked achievements.
+    pub achievements: BTreeSet<String>,
+    /// Which visual effects are turned on.
+    pub effects: EffectSettings,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct HighScores {
+    /// The highest wave reached in the campaign.
+    pub campaign: u32,
+    /// The highest wave reached in endless mode, by seed. Only the seeds
+    /// with the best scores are kept.
+    pub endless: BTreeMap<u64, u32>,
+}
+
+impl HighScores {
+    /// Record reaching wave `score` in an endless run with `seed`. Returns
+    /// whether it is a new high score that was kept.
+    pub fn record_endless(&mut self, seed: u64, score: u32) -> bool {
+        if self.endless.get(&seed).copied().unwrap_or_default() >= score {
+            return false;
+        }
+        self.endless.insert(seed, score);
+        while self.endless.len() > MAX_ENDLESS_SEEDS {
+            let worst = self
+                .endless
+                .iter()
+                .min_by_key(|(_, score)| **score)
+                .map(|(seed, _)| *seed);
+            if let Some(worst) = worst {
+                self.endless.remove(&worst);
+            }
+        }
+        self.endless.contains_key(&seed)
+    }
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct DailyResult {
+    /// The time it took to clear each wave.
+    pub wave_times_ms: Vec<u64>,
+    /// Whether all waves were cleared.
+    pub win: bool,
+    pub difficulty: Difficulty,
+}
+
+impl SaveData {
+    /// The high scores for `difficulty`, if any were reached. There are none
+    /// for custom difficulties.
+    pub fn high_scores(&self, difficulty: Difficulty) -> Option<&HighScores> {
+        if let Difficulty::Custom(_) = difficulty {
+            return None;
+        }
+        self.high_scores.get(difficulty.name())
+    }
+
+    /// The high scores for `difficulty`, to record a new one.
+    pub fn high_scores_mut(&mut self, difficulty: Difficulty) -> &mut HighScores {
+        self.high_scores
+            .entry(difficulty.name().to_string())
+            .or_default()
+    }
+
+    /// The number of campaign waves that can be started from the level
+    /// select: all waves that were reached at any difficulty.
+    pub fn unlocked_waves(&self) -> u32 {
+        let cleared = self.high_scores.values().map(|scores| scores.campaign);
+        cleared.max().unwrap_or(0) + 1
+    }
+
+    /// Load the saved data, falling back to empty data if there is none.
+    pub fn load() -> Self {
+        let Some(content) = read_save() else {
+            return Self::default();
+        };
+        match bevy::asset::ron::from_str(&content) {
+            Ok(data) => data,
+            Err(err) => {
+                warn!("Ignoring invalid save data: {err}");
+                Self::default()
+            }
+        }
+    }
+
+    /// Write the data to disk, or to the local storage of the browser.
+    pub fn save(&self) {
+        match bevy::asset::ron::ser::to_string_pretty(self, default()) {
+            Ok(content) => write_save(&content),
+            Err(err) => warn!("Failed to serialize save data: {err}"),
+        }
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn save_path() -> Option<PathBuf> {
+    Some(data_dir()?.join("save.ron"))
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn read_save() -> Option<String> {
+    let path = save_path()?;
+    match std::fs::read_to_string(&path) {
+        Ok(content) => Some(content),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+        Err(err) => {
+            warn!("Failed to read save file {path:?}: {err}");
+            None
+        }
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn write_save(content: &str) {
+    let Some(path) = save_path() else {
+        return;
+    };
+    let result = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| std::fs::write(&path, content));
+    if let Err(err) = result {
+        warn!("Failed to write save file {path:?}: {err}");
+    }
+}
+
+/// The local storage of the browser, if the page may use it.
+#[cfg(target_family = "wasm")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+#[cfg(target_family = "wasm")]
+fn read_save() -> Option<String> {
+    local_storage()?.get_item(STORAGE_KEY).ok().flatten()
+}
+
+#[cfg(target_family = "wasm")]
+fn write_save(content: &str) {
+    let Some(storage) = local_storage() else {
+        warn!("Failed to save, the local storage is not available");
+        return;
+    };
+    if let Err(err) = storage.set_item(STORAGE_KEY, content) {
+        warn!("Failed to save to the local storage: {err:?}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endless_keeps_the_best_seeds() {
+        let mut scores = HighScores::default();
+        for seed in 0..MAX_ENDLESS_SEEDS as u64 {
+            assert!(scores.record_endless(seed, seed as u32 + 5));
+        }
+        // Worse than every kept seed.
+        assert!(!scores.record_endless(100, 2));
+        assert!(!scores.endless.contains_key(&100));
+        // Better than the worst one, which makes room.
+        assert!(scores.record_endless(101, 10));
+        assert_eq!(scores.endless.len(), MAX_ENDLESS_SEEDS);
+        assert!(!scores.endless.contains_key(&0));
+    }
+
+    #[test]
+    fn endless_only_records_improvements() {
+        let mut scores = HighScores::default();
+        assert!(!scores.record_endless(7, 0));
+        assert!(scores.record_endless(7, 4));
+        assert!(!scores.record_endless(7, 4));
+        assert!(!scores.record_endless(7, 3));
+        assert!(scores.record_endless(7, 5));
+        assert_eq!(scores.endless[&7], 5);
+    }
+}
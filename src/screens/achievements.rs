@@ -0,0 +1,72 @@
+//! A gallery of all achievements, showing which ones are unlocked.
+
+use bevy::prelude::*;
+
+use crate::{
+    demo::achievements::{AchievementAssets, AchievementList},
+    playfield::FillScreen,
+    save::SaveData,
+    screens::Screen,
+    theme::prelude::*,
+};
+
+use super::UiAssets;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Achievements), spawn_achievements_screen);
+}
+
+fn spawn_achievements_screen(
+    mut commands: Commands,
+    assets: Res<UiAssets>,
+    achievement_assets: Option<Res<AchievementAssets>>,
+    lists: Res<Assets<AchievementList>>,
+    save: Res<SaveData>,
+) {
+    commands.spawn((
+        SpriteBundle {
+            texture: assets.background.clone(),
+            ..Default::default()
+        },
+        FillScreen,
+        StateScoped(Screen::Achievements),
+    ));
+
+    let achievements = achievement_assets
+        .and_then(|achievement_assets| lists.get(&achievement_assets.list))
+        .map(|list| list.achievements.as_slice())
+        .unwrap_or_default();
+    let unlocked = achievements
+        .iter()
+        .filter(|achievement| save.achievements.contains(&achievement.id))
+        .count();
+
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Achievements))
+        .with_children(|children| {
+            children.header("Achievements", &assets);
+            children.label(format!("Unlocked {unlocked}/{}", achievements.len()));
+
+            for achievement in achievements {
+                let status = if save.achievements.contains(&achievement.id) {
+                    "Unlocked"
+                } else {
+                    "Locked"
+                };
+                children.label_on_background(
+                    format!(
+                        "{} - {status}\n{}",
+                        achievement.name, achievement.description
+                    ),
+                    2,
+                );
+            }
+
+            children.button("Back").observe(enter_title_screen);
+        });
+}
+
+fn enter_title_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
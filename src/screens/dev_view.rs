@@ -4,18 +4,34 @@ use crate::{screens::Screen, theme::prelude::*};
 use bevy::asset::{LoadedFolder, UntypedAssetId, VisitAssetDependencies};
 use bevy::prelude::*;
 
-use super::title::UiAssets;
+use super::{editor::EditorState, title::UiAssets};
 
 pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<EditLevels>();
     app.add_systems(OnEnter(Screen::Dev), spawn_dev_screen);
     app.load_resource::<LevelsFolder>();
 }
 
+/// Whether the level buttons open the level in the editor instead of playing it.
+#[derive(Resource, Default)]
+struct EditLevels(bool);
+
+impl EditLevels {
+    fn mode_name(&self) -> &'static str {
+        if self.0 {
+            "Mode: Edit"
+        } else {
+            "Mode: Play"
+        }
+    }
+}
+
 fn spawn_dev_screen(
     mut commands: Commands,
     levels: Res<LevelsFolder>,
     folder_assets: Res<Assets<LoadedFolder>>,
     assets: Res<UiAssets>,
+    edit_levels: Res<EditLevels>,
 ) {
     commands
         .ui_root()
@@ -27,6 +43,20 @@ fn spawn_dev_screen(
                 .button("Back to game")
                 .observe(enter_gameplay_screen);
 
+            children
+                .spawn(NodeBundle {
+                    style: Style {
+                        column_gap: Val::Px(10.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|row| {
+                    row.small_button(edit_levels.mode_name())
+                        .observe(toggle_edit_levels);
+                    row.small_button("New level").observe(create_level);
+                });
+
             children
                 .spawn(NodeBundle {
                     style: Style {
@@ -126,16 +156,59 @@ fn enter_gameplay_screen(
 
 fn enter_level(
     level_handle: Handle<LevelDefinition>,
-) -> impl Fn(Trigger<OnPress>, Commands, ResMut<NextState<Screen>>) {
+) -> impl Fn(
+    Trigger<OnPress>,
+    Commands,
+    ResMut<NextState<Screen>>,
+    Res<EditLevels>,
+    Res<Assets<LevelDefinition>>,
+) {
     move |_trigger: Trigger<OnPress>,
           mut commands: Commands,
-          mut next_screen: ResMut<NextState<Screen>>| {
+          mut next_screen: ResMut<NextState<Screen>>,
+          edit_levels: Res<EditLevels>,
+          levels: Res<Assets<LevelDefinition>>| {
+        if edit_levels.0 {
+            let (Some(level), Some(path)) = (levels.get(&level_handle), level_handle.path()) else {
+                return;
+            };
+            commands.insert_resource(EditorState::new(
+                level.clone(),
+                path.path().to_string_lossy(),
+            ));
+            next_screen.set(Screen::Editor);
+            return;
+        }
         commands.insert_resource(DevMode(true));
         next_screen.set(Screen::Gameplay);
         commands.add(SpawnLevel(level_handle.clone()));
     }
 }
 
+fn create_level(
+    _trigger: Trigger<OnPress>,
+    mut commands: Commands,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    commands.insert_resource(EditorState::new(
+        LevelDefinition::default(),
+        "levels/NEW_LEVEL.level.ron",
+    ));
+    next_screen.set(Screen::Editor);
+}
+
+fn toggle_edit_levels(
+    trigger: Trigger<OnPress>,
+    mut edit_levels: ResMut<EditLevels>,
+    children_query: Query<&Children>,
+    mut text_query: Query<&mut Text>,
+) {
+    edit_levels.0 = !edit_levels.0;
+    if let Ok(children) = children_query.get(trigger.entity()) {
+        set_child_text(children, &mut text_query, edit_levels.mode_name());
+    }
+}
+
 #[derive(Clone, Resource, Asset, Reflect)]
 struct LevelsFolder(#[dependency] Handle<LoadedFolder>);
 
@@ -0,0 +1,539 @@
+//! A level editor that is opened from the dev screen.
+//!
+//! Click on the field to place a creature or to select an existing one, then
+//! change its parameters with the buttons on the right. The predicted paths of
+//! all creatures are drawn on top of the field.
+
+use std::time::Duration;
+
+use bevy::{input::common_conditions::input_just_pressed, prelude::*, window::PrimaryWindow};
+
+use crate::{
+    demo::{
+        creature::{CreatureAssets, CreatureDefinition},
+        creature_image::CreatureImage,
+        level::{DevMode, LevelDefinition, SpawnLevel},
+        movement::{draw_path, predict_path, EdgeBehavior, MovementController},
+        movement_pattern::MovementPatternDefinition,
+    },
+    playfield::{window_to_world, FillScreen},
+    screens::{GameplayArea, Screen},
+    theme::{palette::*, prelude::*},
+};
+
+use super::{gameplay::set_gameplay_area, UiAssets};
+
+/// How far ahead the paths of creatures are predicted.
+const PREDICTION_DURATION: Duration = Duration::from_secs(5);
+const PREDICTION_STEP: Duration = Duration::from_millis(16);
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        OnEnter(Screen::Editor),
+        (set_gameplay_area, spawn_editor_background),
+    );
+    app.add_systems(
+        Update,
+        (
+            (
+                select_or_place_creature.run_if(input_just_pressed(MouseButton::Left)),
+                delete_selected_creature.run_if(input_just_pressed(KeyCode::Delete)),
+            ),
+            spawn_editor_ui.run_if(resource_changed::<EditorState>),
+            spawn_preview
+                .run_if(resource_changed::<EditorState>.or_else(resource_changed::<GameplayArea>)),
+            draw_paths,
+        )
+            .chain()
+            .run_if(in_state(Screen::Editor).and_then(resource_exists::<EditorState>))
+            .run_if(resource_exists::<CreatureAssets>),
+    );
+}
+
+/// The level that is being edited.
+#[derive(Resource, Debug, Clone)]
+pub struct EditorState {
+    pub level: LevelDefinition,
+    /// Path of the level file, relative to the assets folder.
+    pub path: String,
+    /// Index of the selected creature in the level.
+    pub selected: Option<usize>,
+    /// The image of newly placed creatures.
+    pub image: CreatureImage,
+}
+
+impl EditorState {
+    pub fn new(level: LevelDefinition, path: impl Into<String>) -> Self {
+        Self {
+            level,
+            path: path.into(),
+            selected: None,
+            image: CreatureImage::Fox,
+        }
+    }
+
+    fn selected_mut(&mut self) -> Option<&mut CreatureDefinition> {
+        self.selected
+            .and_then(|index| self.level.creatures.get_mut(index))
+    }
+
+    fn apply(&mut self, action: EditAction) {
+        if let EditAction::SelectImage(image) = action {
+            self.image = image;
+        }
+        if let EditAction::Delete = action {
+            if let Some(index) = self.selected.take() {
+                self.level.creatures.remove(index);
+            }
+            return;
+        }
+        let Some(creature) = self.selected_mut() else {
+            return;
+        };
+        match (action, &mut creature.movement) {
+            (EditAction::SelectImage(image), _) => creature.image = image,
+            (EditAction::MaxSpeed(delta), _) => {
+                creature.max_speed = (creature.max_speed + delta).max(0.0);
+            }
+            (EditAction::CyclePattern, movement) => {
+                *movement = match movement {
+                    MovementPatternDefinition::Constant { speed } => {
+                        MovementPatternDefinition::Periodic {
+                            duration_ms: 2000,
+                            max_speed: *speed,
+                        }
+                    }
+                    MovementPatternDefinition::Periodic { duration_ms, .. } => {
+                        MovementPatternDefinition::Circle {
+                            duration_ms: *duration_ms,
+                            radius: 0.5,
+                        }
+                    }
+                    MovementPatternDefinition::Circle { .. } => {
+                        MovementPatternDefinition::Constant {
+                            speed: Vec2::new(1.0, 0.0),
+                        }
+                    }
+                };
+            }
+            (
+                EditAction::PatternX(delta),
+                MovementPatternDefinition::Constant { speed: direction }
+                | MovementPatternDefinition::Periodic {
+                    max_speed: direction,
+                    ..
+                },
+            ) => direction.x += delta,
+            (
+                EditAction::PatternY(delta),
+                MovementPatternDefinition::Constant { speed: direction }
+                | MovementPatternDefinition::Periodic {
+                    max_speed: direction,
+                    ..
+                },
+            ) => direction.y += delta,
+            (EditAction::Radius(delta), MovementPatternDefinition::Circle { radius, .. }) => {
+                *radius = (*radius + delta).max(0.05);
+            }
+            (
+                EditAction::PatternDuration(delta),
+                MovementPatternDefinition::Periodic { duration_ms, .. }
+                | MovementPatternDefinition::Circle { duration_ms, .. },
+            ) => {
+                *duration_ms = duration_ms.saturating_add_signed(delta).max(100);
+            }
+            (EditAction::ToggleWrap, _) => creature.wrap = !creature.wrap,
+            (EditAction::ShrinkDuration(delta), _) => {
+                creature.shrink_duration_ms =
+                    creature.shrink_duration_ms.saturating_add_signed(delta);
+            }
+            _ => (),
+        }
+    }
+}
+
+/// A change to the level, triggered by a button.
+#[derive(Debug, Clone, Copy)]
+enum EditAction {
+    SelectImage(CreatureImage),
+    MaxSpeed(f32),
+    CyclePattern,
+    PatternX(f32),
+    PatternY(f32),
+    Radius(f32),
+    PatternDuration(i64),
+    ToggleWrap,
+    ShrinkDuration(i64),
+    Delete,
+}
+
+fn edit(action: EditAction) -> impl Fn(Trigger<OnPress>, ResMut<EditorState>) {
+    move |_trigger: Trigger<OnPress>, mut state: ResMut<EditorState>| {
+        state.apply(action);
+    }
+}
+
+#[derive(Component)]
+struct EditorUi;
+
+#[derive(Component)]
+struct EditorPreview;
+
+/// The predicted path of each creature, in the same order as the level.
+#[derive(Component)]
+struct PredictedPaths(Vec<Vec<Vec2>>);
+
+fn spawn_editor_background(mut commands: Commands, assets: Res<UiAssets>) {
+    commands.spawn((
+        Name::new("Editor Background"),
+        SpriteBundle {
+            texture: assets.background.clone(),
+            ..default()
+        },
+        FillScreen,
+        StateScoped(Screen::Editor),
+    ));
+}
+
+fn spawn_editor_ui(
+    mut commands: Commands,
+    state: Res<EditorState>,
+    ui_query: Query<Entity, With<EditorUi>>,
+) {
+    for entity in &ui_query {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    commands
+        .spawn((
+            Name::new("Editor UI"),
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::SpaceBetween,
+                    padding: UiRect::all(Val::Px(10.0)),
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                ..default()
+            },
+            EditorUi,
+            StateScoped(Screen::Editor),
+        ))
+        .with_children(|root| {
+            root.spawn(column()).with_children(|left| {
+                text(left, &state.path);
+                for image in CreatureImage::ALL {
+                    let name = if image == state.image {
+                        format!("> {image:?}")
+                    } else {
+                        format!("{image:?}")
+                    };
+                    left.small_button(name)
+                        .observe(edit(EditAction::SelectImage(image)));
+                }
+                left.small_button("Test").observe(test_level);
+                #[cfg(not(target_family = "wasm"))]
+                left.small_button("Save").observe(save_level);
+                left.small_button("Back").observe(enter_dev_screen);
+            });
+
+            root.spawn(column()).with_children(|right| {
+                let Some(creature) = state
+                    .selected
+                    .and_then(|index| state.level.creatures.get(index))
+                else {
+                    text(right, "Click to place a creature");
+                    return;
+                };
+                param_row(
+                    right,
+                    format!("Speed: {:.0}", creature.max_speed),
+                    EditAction::MaxSpeed(-50.0),
+                    EditAction::MaxSpeed(50.0),
+                );
+                let (pattern, _) = pattern_name(&creature.movement);
+                right
+                    .small_button(format!("Pattern: {pattern}"))
+                    .observe(edit(EditAction::CyclePattern));
+                match &creature.movement {
+                    MovementPatternDefinition::Constant { speed: direction }
+                    | MovementPatternDefinition::Periodic {
+                        max_speed: direction,
+                        ..
+                    } => {
+                        param_row(
+                            right,
+                            format!("X: {:.1}", direction.x),
+                            EditAction::PatternX(-0.1),
+                            EditAction::PatternX(0.1),
+                        );
+                        param_row(
+                            right,
+                            format!("Y: {:.1}", direction.y),
+                            EditAction::PatternY(-0.1),
+                            EditAction::PatternY(0.1),
+                        );
+                    }
+                    MovementPatternDefinition::Circle { radius, .. } => {
+                        param_row(
+                            right,
+                            format!("Radius: {radius:.2}"),
+                            EditAction::Radius(-0.05),
+                            EditAction::Radius(0.05),
+                        );
+                    }
+                }
+                if let (_, Some(duration_ms)) = pattern_name(&creature.movement) {
+                    param_row(
+                        right,
+                        format!("Period: {duration_ms}ms"),
+                        EditAction::PatternDuration(-250),
+                        EditAction::PatternDuration(250),
+                    );
+                }
+                right
+                    .small_button(if creature.wrap {
+                        "Edge: Wrap"
+                    } else {
+                        "Edge: Bounce"
+                    })
+                    .observe(edit(EditAction::ToggleWrap));
+                param_row(
+                    right,
+                    format!("Shrink: {}ms", creature.shrink_duration_ms),
+                    EditAction::ShrinkDuration(-1000),
+                    EditAction::ShrinkDuration(1000),
+                );
+                right
+                    .small_button("Delete")
+                    .observe(edit(EditAction::Delete));
+            });
+        });
+}
+
+fn pattern_name(pattern: &MovementPatternDefinition) -> (&'static str, Option<u64>) {
+    match pattern {
+        MovementPatternDefinition::Constant { .. } => ("Constant", None),
+        MovementPatternDefinition::Periodic { duration_ms, .. } => ("Periodic", Some(*duration_ms)),
+        MovementPatternDefinition::Circle { duration_ms, .. } => ("Circle", Some(*duration_ms)),
+    }
+}
+
+fn column() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Start,
+            row_gap: Val::Px(4.0),
+            ..default()
+        },
+        ..default()
+    }
+}
+
+fn text(parent: &mut ChildBuilder, value: impl Into<String>) {
+    parent.spawn((
+        Name::new("Editor Text"),
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font_size: 20.0,
+                color: THEME_VANILLA_DARK,
+                ..default()
+            },
+        ),
+    ));
+}
+
+/// A value with buttons to decrease and increase it.
+fn param_row(parent: &mut ChildBuilder, label: String, minus: EditAction, plus: EditAction) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(4.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|row| {
+            row.small_button("-").observe(edit(minus));
+            row.small_button("+").observe(edit(plus));
+            text(row, label);
+        });
+}
+
+fn spawn_preview(
+    mut commands: Commands,
+    state: Res<EditorState>,
+    gameplay_area: Res<GameplayArea>,
+    creature_assets: Res<CreatureAssets>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    preview_query: Query<Entity, With<EditorPreview>>,
+) {
+    for entity in &preview_query {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let mut paths = Vec::new();
+    for creature in &state.level.creatures {
+        let pos = creature.pos.unwrap_or_default();
+        let scale = creature.image.default_scale() * creature.scale;
+        let layout = TextureAtlasLayout::from_grid(
+            creature.image.size(),
+            creature.image.atlas_columns(),
+            creature.image.atlas_rows(),
+            Some(UVec2::splat(1)),
+            None,
+        );
+        commands.spawn((
+            Name::new("Creature Preview"),
+            SpriteBundle {
+                texture: creature_assets.image(creature.image),
+                transform: Transform::from_translation(pos.extend(1.0))
+                    .with_scale(Vec2::splat(scale).extend(1.0)),
+                ..default()
+            },
+            TextureAtlas {
+                layout: texture_atlas_layouts.add(layout),
+                index: 0,
+            },
+            EditorPreview,
+            StateScoped(Screen::Editor),
+        ));
+
+        let edge = if creature.wrap {
+            EdgeBehavior::Wrap
+        } else {
+            EdgeBehavior::Bounce {
+                half_size: creature.image.size().as_vec2() * scale / 2.0,
+            }
+        };
+        paths.push(predict_path(
+            creature.movement.build(),
+            MovementController {
+                max_speed: creature.max_speed,
+                ..default()
+            },
+            pos,
+            edge,
+            gameplay_area.main_area,
+            PREDICTION_DURATION,
+            PREDICTION_STEP,
+        ));
+    }
+    commands.spawn((
+        Name::new("Predicted Paths"),
+        PredictedPaths(paths),
+        EditorPreview,
+        StateScoped(Screen::Editor),
+    ));
+}
+
+fn draw_paths(mut gizmos: Gizmos, state: Res<EditorState>, paths_query: Query<&PredictedPaths>) {
+    for paths in &paths_query {
+        for (index, path) in paths.0.iter().enumerate() {
+            let color = if state.selected == Some(index) {
+                THEME_RED
+            } else {
+                THEME_VANILLA_DARK
+            };
+            draw_path(&mut gizmos, path, color);
+        }
+    }
+}
+
+fn select_or_place_creature(
+    mut state: ResMut<EditorState>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    interaction_query: Query<&Interaction>,
+) {
+    // Clicks on buttons are handled by the buttons.
+    if interaction_query
+        .iter()
+        .any(|interaction| *interaction != Interaction::None)
+    {
+        return;
+    }
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = camera_query.get_single() else {
+        return;
+    };
+    let Some(click) = window
+        .cursor_position()
+        .and_then(|cursor| window_to_world(camera, camera_transform, cursor))
+    else {
+        return;
+    };
+
+    let clicked = state.level.creatures.iter().position(|creature| {
+        let size =
+            creature.image.size().as_vec2() * creature.image.default_scale() * creature.scale;
+        Rect::from_center_size(creature.pos.unwrap_or_default(), size).contains(click)
+    });
+    if let Some(index) = clicked {
+        state.selected = Some(index);
+        return;
+    }
+
+    let image = state.image;
+    state.level.creatures.push(CreatureDefinition {
+        image,
+        max_speed: 400.0,
+        pos: Some(click),
+        movement: MovementPatternDefinition::Constant {
+            speed: Vec2::new(1.0, 0.0),
+        },
+        shrink_duration_ms: 10_000,
+        wrap: false,
+        scale: 1.0,
+        mass: 1.0,
+        radius: None,
+        on_death: Vec::new(),
+    });
+    state.selected = Some(state.level.creatures.len() - 1);
+}
+
+fn delete_selected_creature(mut state: ResMut<EditorState>) {
+    state.apply(EditAction::Delete);
+}
+
+fn test_level(
+    _trigger: Trigger<OnPress>,
+    mut commands: Commands,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut levels: ResMut<Assets<LevelDefinition>>,
+    state: Res<EditorState>,
+) {
+    let level_handle = levels.add(state.level.clone());
+    commands.insert_resource(DevMode(true));
+    next_screen.set(Screen::Gameplay);
+    commands.add(SpawnLevel(level_handle));
+}
+
+/// Write the level back to its file in the assets folder.
+#[cfg(not(target_family = "wasm"))]
+fn save_level(_trigger: Trigger<OnPress>, state: Res<EditorState>) {
+    use bevy::asset::{io::file::FileAssetReader, ron};
+
+    let path = FileAssetReader::get_base_path()
+        .join("assets")
+        .join(&state.path);
+    let config = ron::ser::PrettyConfig::new().struct_names(true);
+    let result = ron::ser::to_string_pretty(&state.level, config)
+        .map_err(|err| err.to_string())
+        .and_then(|content| std::fs::write(&path, content).map_err(|err| err.to_string()));
+    match result {
+        Ok(()) => info!("Saved level to {path:?}"),
+        Err(err) => error!("Failed to save level to {path:?}: {err}"),
+    }
+}
+
+fn enter_dev_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Dev);
+}
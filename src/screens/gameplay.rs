@@ -1,9 +1,7 @@
 //! The screen state for the main gameplay.
 
-use crate::demo::level::{WaveCounter, WaveTimer};
-use crate::theme::prelude::*;
+use crate::playfield::{FillScreen, VisibleArea};
 use bevy::audio::Volume;
-use bevy::window::PrimaryWindow;
 use bevy::{input::common_conditions::input_just_pressed, prelude::*};
 
 use super::title::UiAssets;
@@ -23,6 +21,11 @@ pub(super) fn plugin(app: &mut App) {
         OnEnter(Screen::Gameplay),
         (spawn_game_background, set_gameplay_area),
     );
+    app.add_systems(
+        Update,
+        set_gameplay_area
+            .run_if(resource_exists::<GameplayArea>.and_then(resource_changed::<VisibleArea>)),
+    );
     app.add_systems(OnExit(Screen::Gameplay), (stop_music, remove_background));
 
     app.add_systems(
@@ -31,45 +34,6 @@ pub(super) fn plugin(app: &mut App) {
             .run_if(in_state(Screen::Gameplay).and_then(input_just_pressed(KeyCode::Escape))),
     );
     app.add_systems(Update, dev_mode_portal);
-    app.add_systems(
-        Update,
-        update_wave_timer.run_if(resource_exists::<WaveTimer>),
-    );
-    app.add_systems(
-        Update,
-        update_wave_number.run_if(resource_exists::<WaveCounter>),
-    );
-}
-
-#[derive(Component, Debug, Clone, Reflect)]
-struct WaveTimerLabel;
-
-#[derive(Component, Debug, Clone, Reflect)]
-struct WaveNumber;
-
-// Modifies the UI to show the time left in the wave.
-fn update_wave_timer(
-    timer: Res<WaveTimer>,
-    parent_query: Query<&Children, With<WaveTimerLabel>>,
-    mut child_query: Query<&mut Text>,
-) {
-    let children = parent_query.single();
-    for &child in children.iter() {
-        let mut text = child_query.get_mut(child).unwrap();
-        text.sections[0].value = format!("Time left: {:.0}s", timer.0.remaining_secs().ceil());
-    }
-}
-
-fn update_wave_number(
-    wave_counter: Res<WaveCounter>,
-    parent_query: Query<&Children, With<WaveNumber>>,
-    mut child_query: Query<&mut Text>,
-) {
-    let children = parent_query.single();
-    for &child in children.iter() {
-        let mut text = child_query.get_mut(child).unwrap();
-        text.sections[0].value = format!("Wave: {}", wave_counter.wave);
-    }
 }
 
 fn spawn_game_background(mut commands: Commands, assets: Res<UiAssets>) {
@@ -78,20 +42,7 @@ fn spawn_game_background(mut commands: Commands, assets: Res<UiAssets>) {
             texture: assets.background.clone(),
             ..Default::default()
         })
-        .insert(Background);
-
-    commands
-        .top_panel()
-        .insert(StateScoped(Screen::Gameplay))
-        .with_children(|children| {
-            children
-                .header("Time left: 30s".to_owned(), &assets)
-                .insert(WaveTimerLabel);
-
-            children
-                .header("Wave: 1".to_owned(), &assets)
-                .insert(WaveNumber);
-        });
+        .insert((Background, FillScreen));
 }
 
 fn remove_background(mut commands: Commands, query: Query<Entity, With<Background>>) {
@@ -145,11 +96,8 @@ fn stop_music(mut commands: Commands, mut music: ResMut<GameplayMusic>) {
     }
 }
 
-fn set_gameplay_area(mut commands: Commands, window_query: Query<&Window, With<PrimaryWindow>>) {
-    let Ok(window) = window_query.get_single() else {
-        return;
-    };
-    let mut main_area = Rect::from_center_size(Vec2::ZERO, window.size());
+pub(super) fn set_gameplay_area(mut commands: Commands, visible_area: Res<VisibleArea>) {
+    let mut main_area = visible_area.0;
     // subtract a few px for the header
     main_area.max.y -= HEADER_SIZE;
     commands.insert_resource(GameplayArea { main_area });
@@ -0,0 +1,61 @@
+//! The screen between two turns of a versus game, so the players can hand
+//! over the mouse.
+
+use bevy::prelude::*;
+
+use crate::{
+    demo::versus::{Versus, VERSUS_ROUNDS},
+    playfield::FillScreen,
+    screens::Screen,
+    theme::prelude::*,
+};
+
+use super::UiAssets;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Handoff), spawn_handoff_screen);
+}
+
+fn spawn_handoff_screen(mut commands: Commands, assets: Res<UiAssets>, versus: Res<Versus>) {
+    commands.spawn((
+        SpriteBundle {
+            texture: assets.background.clone(),
+            ..Default::default()
+        },
+        FillScreen,
+        StateScoped(Screen::Handoff),
+    ));
+
+    let scores = versus
+        .scores
+        .iter()
+        .enumerate()
+        .map(|(player, score)| format!("Player {}: {} points", player + 1, score.points))
+        .collect::<Vec<_>>()
+        .join("   ");
+
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Handoff))
+        .with_children(|children| {
+            children.header(format!("Player {}", versus.player + 1), &assets);
+            children.label_on_background(
+                format!(
+                    "Round {} of {VERSUS_ROUNDS}\nPass the mouse to player {}\n{scores}",
+                    versus.round,
+                    versus.player + 1
+                ),
+                3,
+            );
+            children.button("Ready").observe(enter_gameplay_screen);
+            children.button("Menu").observe(enter_title_screen);
+        });
+}
+
+fn enter_gameplay_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Gameplay);
+}
+
+fn enter_title_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
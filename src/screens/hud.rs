@@ -0,0 +1,377 @@
+//! The HUD shown during gameplay.
+//!
+//! It is updated from gameplay events and resources, and it doesn't mind if
+//! parts of it are missing.
+
+use bevy::prelude::*;
+
+use crate::{
+    demo::{
+        creature::{Creature, DeathAnimation},
+        events::{CreatureKilled, WaveCleared, WaveStarted},
+        level::WaveTimer,
+        players::{PlayerId, Players},
+        powerup::ActivePowerUps,
+        scoring::PlayerScores,
+    },
+    screens::{GameScore, Screen},
+    theme::{palette::*, prelude::*},
+    AppSet,
+};
+
+use super::UiAssets;
+
+/// Below this many seconds left, the timer bar turns red.
+const TIMER_WARNING_SECS: f32 = 5.0;
+/// How long wave banners are shown.
+const BANNER_DURATION_SECS: f32 = 1.5;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<WaveKills>();
+    app.add_systems(OnEnter(Screen::Gameplay), (reset_wave_kills, spawn_hud));
+    app.add_systems(
+        Update,
+        (
+            count_wave_kills,
+            update_wave_number,
+            update_score,
+            update_timer_bar.run_if(resource_exists_and_changed::<WaveTimer>),
+            // The creatures only change when a wave starts and when they die.
+            update_stats.run_if(
+                resource_exists_and_changed::<GameScore>.or_else(resource_changed::<WaveKills>),
+            ),
+            update_player_scores.run_if(resource_changed::<PlayerScores>),
+            update_power_up_indicator.run_if(resource_changed::<ActivePowerUps>),
+            spawn_banners,
+            animate_banners,
+        )
+            .chain()
+            .after(AppSet::Update)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Reflect)]
+struct WaveNumber;
+
+#[derive(Component, Debug, Clone, Reflect)]
+struct ScoreLabel;
+
+#[derive(Component, Debug, Clone, Reflect)]
+struct TimerBar;
+
+#[derive(Component, Debug, Clone, Reflect)]
+struct StatsLabel;
+
+#[derive(Component, Debug, Clone, Reflect)]
+struct PlayerScoresLabel;
+
+#[derive(Component, Debug, Clone, Reflect)]
+struct PowerUpIndicator;
+
+/// A message in the middle of the screen that grows and fades out.
+#[derive(Component, Debug, Clone, Reflect)]
+struct Banner {
+    /// Time before the banner appears.
+    delay: Timer,
+    timer: Timer,
+}
+
+/// Creatures killed in the current wave, to show how many the wave had.
+#[derive(Resource, Debug, Clone, Default, Reflect)]
+struct WaveKills(u32);
+
+fn spawn_hud(mut commands: Commands, assets: Res<UiAssets>, players: Res<Players>) {
+    commands
+        .top_panel()
+        .insert(StateScoped(Screen::Gameplay))
+        .with_children(|children| {
+            children.header("Wave: 1", &assets).insert(WaveNumber);
+
+            children
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(240.0),
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(4.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|center| {
+                    center
+                        .spawn((
+                            Name::new("Timer"),
+                            NodeBundle {
+                                style: Style {
+                                    width: Val::Percent(100.0),
+                                    height: Val::Px(16.0),
+                                    ..default()
+                                },
+                                background_color: BackgroundColor(THEME_VANILLA_DARK),
+                                ..default()
+                            },
+                        ))
+                        .with_children(|timer| {
+                            timer.spawn((
+                                Name::new("Timer Bar"),
+                                NodeBundle {
+                                    style: Style {
+                                        width: Val::Percent(100.0),
+                                        height: Val::Percent(100.0),
+                                        ..default()
+                                    },
+                                    background_color: BackgroundColor(THEME_MALACHITE),
+                                    ..default()
+                                },
+                                TimerBar,
+                            ));
+                        });
+                    center.spawn((
+                        Name::new("Stats"),
+                        TextBundle::from_section(
+                            "",
+                            TextStyle {
+                                font_size: 20.0,
+                                color: Color::BLACK,
+                                ..default()
+                            },
+                        ),
+                        StatsLabel,
+                    ));
+                    if players.is_multiplayer() {
+                        center.spawn((
+                            Name::new("Player Scores"),
+                            // Dark, so that the light player colors stand out.
+                            TextBundle::default()
+                                .with_background_color(Color::BLACK.with_alpha(0.6)),
+                            PlayerScoresLabel,
+                        ));
+                    }
+                    center.spawn((
+                        Name::new("Power-ups"),
+                        TextBundle::default(),
+                        PowerUpIndicator,
+                    ));
+                });
+
+            children.header("Score: 0", &assets).insert(ScoreLabel);
+        });
+}
+
+fn reset_wave_kills(mut kills: ResMut<WaveKills>) {
+    kills.0 = 0;
+}
+
+fn count_wave_kills(
+    mut started_events: EventReader<WaveStarted>,
+    mut killed_events: EventReader<CreatureKilled>,
+    mut kills: ResMut<WaveKills>,
+) {
+    if started_events.read().count() > 0 {
+        kills.0 = 0;
+    }
+    // Only touch the kills when they change, the stats wait for that.
+    let killed = killed_events.read().count() as u32;
+    if killed > 0 {
+        kills.0 += killed;
+    }
+}
+
+fn update_wave_number(
+    mut events: EventReader<WaveStarted>,
+    label_query: Query<&Children, With<WaveNumber>>,
+    mut text_query: Query<&mut Text>,
+) {
+    let Some(event) = events.read().last() else {
+        return;
+    };
+    for children in &label_query {
+        set_child_text(children, &mut text_query, format!("Wave: {}", event.wave));
+    }
+}
+
+fn update_score(
+    game_score: Res<GameScore>,
+    label_query: Query<&Children, With<ScoreLabel>>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !game_score.is_changed() {
+        return;
+    }
+    for children in &label_query {
+        set_child_text(
+            children,
+            &mut text_query,
+            format!("Score: {}", game_score.points),
+        );
+    }
+}
+
+fn update_timer_bar(
+    timer: Res<WaveTimer>,
+    mut bar_query: Query<(&mut Style, &mut BackgroundColor), With<TimerBar>>,
+) {
+    let remaining = timer.0.remaining_secs();
+    let color = if remaining < TIMER_WARNING_SECS {
+        THEME_RED
+    } else {
+        THEME_MALACHITE
+    };
+    let width = Val::Percent(timer.0.fraction_remaining() * 100.0);
+    for (mut style, mut background) in &mut bar_query {
+        // Changing the style lays out the UI again.
+        if style.width != width {
+            style.width = width;
+        }
+        background.set_if_neq(BackgroundColor(color));
+    }
+}
+
+fn update_stats(
+    game_score: Res<GameScore>,
+    kills: Res<WaveKills>,
+    creatures: Query<(), (With<Creature>, Without<DeathAnimation>)>,
+    mut label_query: Query<&mut Text, With<StatsLabel>>,
+) {
+    let remaining = creatures.iter().len() as u32;
+    let total = remaining + kills.0;
+    let value = format!(
+        "Creatures {remaining}/{total}  Combo x{}  Shots {}  Misses {}",
+        game_score.combo, game_score.shots, game_score.misses
+    );
+    for mut text in &mut label_query {
+        if text.sections[0].value != value {
+            text.sections[0].value.clone_from(&value);
+        }
+    }
+}
+
+// Shows the points and combo of every player in their color.
+fn update_player_scores(
+    player_scores: Res<PlayerScores>,
+    mut label_query: Query<&mut Text, With<PlayerScoresLabel>>,
+) {
+    for mut text in &mut label_query {
+        text.sections = player_scores
+            .0
+            .iter()
+            .enumerate()
+            .map(|(player, score)| {
+                let player = PlayerId(player);
+                TextSection::new(
+                    format!("{} {} x{}  ", player.name(), score.points, score.combo),
+                    TextStyle {
+                        font_size: 20.0,
+                        color: player.color(),
+                        ..default()
+                    },
+                )
+            })
+            .collect();
+    }
+}
+
+// Lists the active power-ups with the seconds they have left.
+fn update_power_up_indicator(
+    power_ups: Res<ActivePowerUps>,
+    mut indicator_query: Query<&mut Text, With<PowerUpIndicator>>,
+) {
+    for mut text in &mut indicator_query {
+        text.sections = power_ups
+            .0
+            .iter()
+            .map(|(kind, timer)| {
+                TextSection::new(
+                    format!("{} {:.0}s\n", kind.label(), timer.remaining_secs().ceil()),
+                    TextStyle {
+                        font_size: 24.0,
+                        color: kind.color(),
+                        ..default()
+                    },
+                )
+            })
+            .collect();
+    }
+}
+
+fn spawn_banners(
+    mut commands: Commands,
+    assets: Res<UiAssets>,
+    mut started_events: EventReader<WaveStarted>,
+    mut cleared_events: EventReader<WaveCleared>,
+    banners: Query<Entity, With<Banner>>,
+) {
+    let cleared = cleared_events.read().last().is_some();
+    let started = started_events.read().last().map(|event| event.wave);
+    if !cleared && started.is_none() {
+        return;
+    }
+
+    for entity in &banners {
+        commands.entity(entity).despawn_recursive();
+    }
+    // The next wave starts right when one is cleared, so its banner waits
+    // until the other one is gone.
+    let mut delay = 0.0;
+    if cleared {
+        spawn_banner(&mut commands, &assets, "Wave cleared!".to_string(), delay);
+        delay += BANNER_DURATION_SECS;
+    }
+    if let Some(wave) = started {
+        spawn_banner(&mut commands, &assets, format!("Wave {wave}"), delay);
+    }
+}
+
+fn spawn_banner(commands: &mut Commands, assets: &UiAssets, message: String, delay: f32) {
+    commands
+        .ui_root()
+        .insert((
+            Name::new("Banner"),
+            StateScoped(Screen::Gameplay),
+            Banner {
+                delay: Timer::from_seconds(delay, TimerMode::Once),
+                timer: Timer::from_seconds(BANNER_DURATION_SECS, TimerMode::Once),
+            },
+        ))
+        .with_children(|children| {
+            children.large_message(message, assets);
+        });
+}
+
+fn animate_banners(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut banner_query: Query<(
+        Entity,
+        &mut Banner,
+        &mut Transform,
+        &mut Visibility,
+        &Children,
+    )>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (entity, mut banner, mut transform, mut visibility, children) in &mut banner_query {
+        if !banner.delay.tick(time.delta()).finished() {
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+        *visibility = Visibility::Inherited;
+        banner.timer.tick(time.delta());
+        if banner.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let t = banner.timer.fraction();
+        transform.scale = Vec3::splat(0.8 + 0.4 * t);
+        // Stay opaque for the first half, then fade out.
+        let alpha = (2.0 - 2.0 * t).min(1.0);
+        for &child in children {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                for section in &mut text.sections {
+                    section.style.color.set_alpha(alpha);
+                }
+            }
+        }
+    }
+}
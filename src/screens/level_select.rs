@@ -0,0 +1,94 @@
+//! A screen to start the campaign at a later wave.
+
+use bevy::prelude::*;
+
+use crate::{
+    demo::level::{GameMode, Levels, StartWave},
+    playfield::FillScreen,
+    save::SaveData,
+    screens::Screen,
+    theme::prelude::*,
+};
+
+use super::UiAssets;
+
+/// Number of wave buttons in each row.
+const WAVES_PER_ROW: u32 = 6;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::LevelSelect), spawn_level_select_screen);
+}
+
+fn spawn_level_select_screen(
+    mut commands: Commands,
+    assets: Res<UiAssets>,
+    levels: Res<Levels>,
+    save: Res<SaveData>,
+) {
+    commands.spawn((
+        SpriteBundle {
+            texture: assets.background.clone(),
+            ..Default::default()
+        },
+        FillScreen,
+        StateScoped(Screen::LevelSelect),
+    ));
+
+    // Dev builds can start at any wave.
+    let unlocked = if cfg!(feature = "dev") {
+        levels.count()
+    } else {
+        save.unlocked_waves().min(levels.count())
+    };
+
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::LevelSelect))
+        .with_children(|children| {
+            children.header("Select wave", &assets);
+
+            for row_start in (1..=unlocked).step_by(WAVES_PER_ROW as usize) {
+                children
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Row,
+                            column_gap: Val::Px(6.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        let row_end = (row_start + WAVES_PER_ROW - 1).min(unlocked);
+                        for wave in row_start..=row_end {
+                            row.small_button(wave.to_string())
+                                .observe(start_at_wave(wave));
+                        }
+                    });
+            }
+            if unlocked < levels.count() {
+                children.label(format!(
+                    "Reach more waves to unlock them ({unlocked}/{})",
+                    levels.count()
+                ));
+            }
+
+            children.button("Back").observe(enter_title_screen);
+        });
+}
+
+fn start_at_wave(
+    wave: u32,
+) -> impl Fn(Trigger<OnPress>, ResMut<NextState<Screen>>, ResMut<GameMode>, ResMut<StartWave>) {
+    move |_trigger: Trigger<OnPress>,
+          mut next_screen: ResMut<NextState<Screen>>,
+          mut mode: ResMut<GameMode>,
+          mut start_wave: ResMut<StartWave>| {
+        *mode = GameMode::Campaign;
+        *start_wave = StartWave(wave);
+        next_screen.set(Screen::Gameplay);
+    }
+}
+
+fn enter_title_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
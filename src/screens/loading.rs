@@ -4,6 +4,7 @@
 use bevy::prelude::*;
 
 use crate::{
+    config::AppConfig,
     demo::creature::CreatureAssets,
     screens::{credits::CreditsMusic, gameplay::GameplayMusic, Screen},
     theme::{interaction::InteractionAssets, prelude::*},
@@ -30,8 +31,12 @@ fn spawn_loading_screen(mut commands: Commands) {
         });
 }
 
-fn continue_to_title_screen(mut next_screen: ResMut<NextState<Screen>>) {
-    next_screen.set(Screen::Title);
+fn continue_to_title_screen(mut next_screen: ResMut<NextState<Screen>>, config: Res<AppConfig>) {
+    if config.windowed_dev {
+        next_screen.set(Screen::Dev);
+    } else {
+        next_screen.set(Screen::Title);
+    }
 }
 
 fn all_assets_loaded(
@@ -1,23 +1,61 @@
 //! The game's main screen states and transitions between them.
 
+mod achievements;
 mod credits;
 mod dev_view;
+mod editor;
 mod gameplay;
+mod handoff;
+mod hud;
+mod level_select;
 mod loading;
+mod party;
+mod pause;
 mod score;
+mod settings;
 mod splash;
 mod title;
 
+use std::time::Duration;
+
 use bevy::prelude::*;
 pub use gameplay::GameplayArea;
+pub use pause::{is_long_press, is_paused};
 pub use title::UiAssets;
 
 use crate::theme::palette::THEME_VANILLA;
 
-#[derive(Resource, Reflect, Clone, Default)]
+#[derive(Resource, Reflect, Debug, Clone, Default)]
 pub struct GameScore {
+    /// The last wave that was cleared, counted from 1, so that runs started
+    /// at a later wave reach the same score. 0 if none was.
     pub score: u32,
+    /// The wave the run started at, counted from 1. 0 until it started.
+    pub first_wave: u32,
     pub win: bool,
+    /// Points for killed creatures.
+    pub points: u32,
+    /// Number of hits in a row.
+    pub combo: u32,
+    pub shots: u32,
+    /// Bullets that hit at least one creature.
+    pub hits: u32,
+    pub misses: u32,
+    /// The time it took to clear each wave.
+    pub wave_times: Vec<Duration>,
+    /// Whether the run was changed from the dev console, so it doesn't count
+    /// for high scores and achievements.
+    pub console_used: bool,
+}
+
+impl GameScore {
+    /// The number of waves that were cleared in this run.
+    pub fn waves_cleared(&self) -> u32 {
+        if self.first_wave == 0 {
+            return 0;
+        }
+        (self.score + 1).saturating_sub(self.first_wave)
+    }
 }
 
 pub(super) fn plugin(app: &mut App) {
@@ -31,11 +69,19 @@ pub(super) fn plugin(app: &mut App) {
     app.add_plugins((
         credits::plugin,
         gameplay::plugin,
+        hud::plugin,
         loading::plugin,
         splash::plugin,
         title::plugin,
         score::plugin,
         dev_view::plugin,
+        editor::plugin,
+        level_select::plugin,
+        achievements::plugin,
+        handoff::plugin,
+        party::plugin,
+        pause::plugin,
+        settings::plugin,
     ));
 }
 
@@ -47,7 +93,13 @@ pub enum Screen {
     Loading,
     Title,
     Credits,
+    Settings,
+    Achievements,
+    LevelSelect,
     Gameplay,
+    Handoff,
+    Party,
     Score,
     Dev,
+    Editor,
 }
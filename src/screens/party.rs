@@ -0,0 +1,114 @@
+//! The screen where players join a local multiplayer game.
+//!
+//! The first player uses the mouse. Others join by pressing Enter on the
+//! keyboard or the south button on a gamepad.
+
+use bevy::{input::gamepad::GamepadButtonType, prelude::*};
+
+use crate::{
+    config::AppConfig,
+    demo::{
+        level::GameMode,
+        players::{PlayerId, PlayerInput, Players},
+    },
+    playfield::FillScreen,
+    screens::Screen,
+    theme::prelude::*,
+};
+
+use super::UiAssets;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Party), spawn_party_screen);
+    app.add_systems(
+        Update,
+        (
+            join_players,
+            update_player_list.run_if(resource_changed::<Players>),
+        )
+            .chain()
+            .run_if(in_state(Screen::Party)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Reflect)]
+struct PlayerList;
+
+fn spawn_party_screen(mut commands: Commands, assets: Res<UiAssets>) {
+    commands.spawn((
+        SpriteBundle {
+            texture: assets.background.clone(),
+            ..Default::default()
+        },
+        FillScreen,
+        StateScoped(Screen::Party),
+    ));
+
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Party))
+        .with_children(|children| {
+            children.header("Party", &assets);
+            children.label_on_background("Press Enter or the south button on a gamepad to join", 3);
+            children.spawn((
+                Name::new("Player List"),
+                TextBundle::default().with_background_color(Color::BLACK.with_alpha(0.6)),
+                PlayerList,
+            ));
+            children.button("Start").observe(enter_gameplay_screen);
+            children.button("Back").observe(enter_title_screen);
+        });
+}
+
+fn join_players(
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    mut players: ResMut<Players>,
+) {
+    if keys.just_pressed(KeyCode::Enter) {
+        players.join(PlayerInput::Keyboard);
+    }
+    for gamepad in gamepads.iter() {
+        if gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::South)) {
+            players.join(PlayerInput::Gamepad(gamepad));
+        }
+    }
+}
+
+fn update_player_list(players: Res<Players>, mut list_query: Query<&mut Text, With<PlayerList>>) {
+    for mut text in &mut list_query {
+        text.sections = players
+            .0
+            .iter()
+            .enumerate()
+            .map(|(player, input)| {
+                let player = PlayerId(player);
+                TextSection::new(
+                    format!("{}: {}\n", player.name(), input.name()),
+                    TextStyle {
+                        font_size: 32.0,
+                        color: player.color(),
+                        ..default()
+                    },
+                )
+            })
+            .collect();
+    }
+}
+
+fn enter_gameplay_screen(
+    _trigger: Trigger<OnPress>,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut mode: ResMut<GameMode>,
+    config: Res<AppConfig>,
+) {
+    *mode = GameMode::Party {
+        seed: config.seed.unwrap_or_else(rand::random),
+    };
+    next_screen.set(Screen::Gameplay);
+}
+
+fn enter_title_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
@@ -0,0 +1,128 @@
+//! Pausing the game.
+//!
+//! On touch screens, a long press anywhere pauses and tapping resumes. With a
+//! keyboard, P does both. While paused, virtual time stands still and none of
+//! the [`AppSet`](crate::AppSet)s run.
+
+use std::time::Duration;
+
+use bevy::{
+    input::{common_conditions::input_just_pressed, touch::Touch},
+    prelude::*,
+    utils::HashMap,
+};
+
+use crate::{screens::Screen, theme::prelude::*};
+
+use super::UiAssets;
+
+/// How long a touch has to be held to pause.
+const LONG_PRESS: Duration = Duration::from_millis(600);
+/// A touch that moves further than this, in logical pixels, isn't a long press.
+const LONG_PRESS_MAX_DISTANCE: f32 = 24.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnExit(Screen::Gameplay), resume);
+    app.add_systems(
+        Update,
+        (
+            toggle_pause.run_if(input_just_pressed(KeyCode::KeyP)),
+            pause_on_long_press.run_if(not(is_paused)),
+            resume_on_tap.run_if(is_paused),
+            show_pause_overlay,
+        )
+            .chain()
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Reflect)]
+struct PauseOverlay;
+
+pub fn is_paused(time: Res<Time<Virtual>>) -> bool {
+    time.is_paused()
+}
+
+/// Whether a touch that has been held for `held` is a long press, which
+/// pauses instead of throwing.
+pub fn is_long_press(touch: &Touch, held: Duration) -> bool {
+    held >= LONG_PRESS && touch.distance().length() <= LONG_PRESS_MAX_DISTANCE
+}
+
+fn resume(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+fn toggle_pause(mut time: ResMut<Time<Virtual>>) {
+    if time.is_paused() {
+        time.unpause();
+    } else {
+        time.pause();
+    }
+}
+
+fn pause_on_long_press(
+    real_time: Res<Time<Real>>,
+    touches: Res<Touches>,
+    mut presses: Local<HashMap<u64, Duration>>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    let now = real_time.elapsed();
+    for touch in touches.iter_just_pressed() {
+        presses.insert(touch.id(), now);
+    }
+    // Forget touches that ended or moved, those are throws or swipes.
+    presses.retain(|&id, _| {
+        touches
+            .get_pressed(id)
+            .is_some_and(|touch| touch.distance().length() <= LONG_PRESS_MAX_DISTANCE)
+    });
+    if presses.values().any(|&start| now - start >= LONG_PRESS) {
+        presses.clear();
+        time.pause();
+    }
+}
+
+/// Resume when a tap or click that started while paused ends, so the finger
+/// that paused doesn't resume right away and the tap doesn't throw.
+fn resume_on_tap(
+    touches: Res<Touches>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut tapped: Local<bool>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    if touches.any_just_pressed() || mouse.just_pressed(MouseButton::Left) {
+        *tapped = true;
+    }
+    if *tapped && (touches.any_just_released() || mouse.just_released(MouseButton::Left)) {
+        *tapped = false;
+        time.unpause();
+    }
+}
+
+fn show_pause_overlay(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    assets: Res<UiAssets>,
+    overlay_query: Query<Entity, With<PauseOverlay>>,
+) {
+    match (time.is_paused(), overlay_query.get_single()) {
+        (true, Err(_)) => {
+            commands
+                .ui_root()
+                .insert((
+                    Name::new("Pause Overlay"),
+                    PauseOverlay,
+                    StateScoped(Screen::Gameplay),
+                ))
+                .with_children(|children| {
+                    children.large_message("Paused", &assets);
+                    children.label_on_background("Tap or press P to resume", 1);
+                });
+        }
+        (false, Ok(entity)) => {
+            commands.entity(entity).despawn_recursive();
+        }
+        _ => {}
+    }
+}
@@ -2,43 +2,483 @@
 
 use bevy::prelude::*;
 
-use crate::{screens::Screen, theme::prelude::*};
+use crate::{
+    clipboard,
+    config::AppConfig,
+    demo::{
+        daily::{self, DAILY_WAVES},
+        difficulty::Difficulty,
+        level::GameMode,
+        players::Players,
+        scoring::PlayerScores,
+        stats::{self, RunStats},
+        versus::{Versus, VERSUS_ROUNDS},
+    },
+    playfield::FillScreen,
+    save::SaveData,
+    screens::Screen,
+    theme::{palette::*, prelude::*},
+};
 
 use super::{GameScore, UiAssets};
 
+/// Columns and rows of the heatmap of bullet targets.
+const HEATMAP_SIZE: (usize, usize) = (16, 10);
+/// Size of one heatmap cell in pixels.
+const HEATMAP_CELL: f32 = 20.0;
+/// Number of waves listed in the waves tab. Endless runs can be long.
+const MAX_LISTED_WAVES: usize = 24;
+
 pub(super) fn plugin(app: &mut App) {
-    app.add_systems(OnEnter(Screen::Score), spawn_score_screen);
+    app.init_resource::<ScoreTab>();
+    app.add_systems(
+        OnEnter(Screen::Score),
+        (record_high_score, reset_score_tab, spawn_score_screen).chain(),
+    );
+    app.add_systems(
+        Update,
+        show_score_tab.run_if(in_state(Screen::Score).and_then(resource_changed::<ScoreTab>)),
+    );
 }
 
-fn spawn_score_screen(mut commands: Commands, game_score: Res<GameScore>, assets: Res<UiAssets>) {
-    commands.spawn(SpriteBundle {
-        texture: assets.background.clone(),
-        ..Default::default()
-    });
+/// The tab that is shown on the score screen.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum ScoreTab {
+    #[default]
+    Result,
+    Stats,
+    Waves,
+    Heatmap,
+}
+
+impl ScoreTab {
+    const ALL: [ScoreTab; 4] = [
+        ScoreTab::Result,
+        ScoreTab::Stats,
+        ScoreTab::Waves,
+        ScoreTab::Heatmap,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            ScoreTab::Result => "Result",
+            ScoreTab::Stats => "Stats",
+            ScoreTab::Waves => "Waves",
+            ScoreTab::Heatmap => "Heatmap",
+        }
+    }
+}
+
+/// The node that holds the content of the selected [`ScoreTab`].
+#[derive(Component, Debug, Clone, Copy)]
+struct ScoreTabContent;
+
+fn record_high_score(
+    game_score: Res<GameScore>,
+    mode: Res<GameMode>,
+    difficulty: Res<Difficulty>,
+    config: Res<AppConfig>,
+    mut save: ResMut<SaveData>,
+) {
+    // Custom levels and campaigns don't count, and would unlock the waves of
+    // the real campaign. Neither do runs changed from the dev console.
+    if config.level.is_some() || config.campaign.is_some() || game_score.console_used {
+        return;
+    }
+    // Scores at custom difficulties can't be compared.
+    if let Difficulty::Custom(_) = *difficulty {
+        return;
+    }
+    let high_scores = save.high_scores_mut(*difficulty);
+    let new_best = match *mode {
+        GameMode::Campaign if game_score.score > high_scores.campaign => {
+            high_scores.campaign = game_score.score;
+            true
+        }
+        GameMode::Campaign => false,
+        GameMode::Endless { seed } => high_scores.record_endless(seed, game_score.score),
+        // The daily challenge records its result when the game ends.
+        GameMode::Daily { .. } => return,
+        // Versus games are about beating each other.
+        GameMode::Versus { .. } => return,
+        // The score of a party belongs to all of its players.
+        GameMode::Party { .. } => return,
+    };
+    if new_best {
+        save.save();
+    }
+}
+
+fn reset_score_tab(mut tab: ResMut<ScoreTab>) {
+    *tab = ScoreTab::Result;
+}
+
+fn spawn_score_screen(mut commands: Commands, mode: Res<GameMode>, assets: Res<UiAssets>) {
+    commands.spawn((
+        SpriteBundle {
+            texture: assets.background.clone(),
+            ..Default::default()
+        },
+        FillScreen,
+    ));
 
     commands
         .ui_root()
         .insert(StateScoped(Screen::Score))
         .with_children(|children| {
-            let message = if game_score.win {
-                "You've cleared all waves.\nCongratulations!".to_string()
-            } else {
-                format!("You've reached wave {}.\nTry again!", game_score.score)
-            };
-            children.label_on_background(&message, 2);
-
-            children.button("Restart").observe(enter_gameplay_screen);
-            children.button("Menu").observe(enter_title_screen);
-
-            #[cfg(not(target_family = "wasm"))]
-            children.button("Exit").observe(exit_app);
+            children
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        column_gap: Val::Px(6.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|tabs| {
+                    for tab in ScoreTab::ALL {
+                        tabs.small_button(tab.name()).observe(select_tab(tab));
+                    }
+                    #[cfg(not(target_family = "wasm"))]
+                    tabs.small_button("Export").observe(export_stats);
+                });
+
+            children.spawn((
+                Name::new("Tab Content"),
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(10.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                ScoreTabContent,
+            ));
+
+            children
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        column_gap: Val::Px(10.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|buttons| {
+                    if matches!(*mode, GameMode::Daily { .. }) {
+                        // There is only one attempt per day, so there is no restart.
+                        buttons.button("Share").observe(copy_daily_result);
+                    } else {
+                        buttons.button("Restart").observe(enter_gameplay_screen);
+                    }
+                    buttons.button("Menu").observe(enter_title_screen);
+
+                    #[cfg(not(target_family = "wasm"))]
+                    buttons.button("Exit").observe(exit_app);
+                });
         });
 }
 
-fn enter_gameplay_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+fn show_score_tab(
+    mut commands: Commands,
+    tab: Res<ScoreTab>,
+    content_query: Query<Entity, With<ScoreTabContent>>,
+    game_score: Res<GameScore>,
+    stats: Res<RunStats>,
+    mode: Res<GameMode>,
+    difficulty: Res<Difficulty>,
+    versus: Res<Versus>,
+    players: Res<Players>,
+    player_scores: Res<PlayerScores>,
+    save: Res<SaveData>,
+) {
+    let Ok(content) = content_query.get_single() else {
+        return;
+    };
+    commands
+        .entity(content)
+        .despawn_descendants()
+        .with_children(|children| match *tab {
+            ScoreTab::Result => {
+                let (message, lines) = if matches!(*mode, GameMode::Versus { .. }) {
+                    versus_text(&versus)
+                } else {
+                    result_text(&game_score, *mode, *difficulty, &save)
+                };
+                children.label_on_background(message, lines);
+                if players.is_multiplayer() {
+                    let message = players_text(&player_scores);
+                    children.label_on_background(&message, message.lines().count());
+                }
+            }
+            ScoreTab::Stats => {
+                let message = stats_text(&game_score, &stats);
+                children.label_on_background(&message, message.lines().count());
+            }
+            ScoreTab::Waves => {
+                let message = waves_text(&stats);
+                children.label_on_background(&message, message.lines().count());
+            }
+            ScoreTab::Heatmap => {
+                children.label_on_background("Where bullets were thrown", 1);
+                spawn_heatmap(children, &stats);
+            }
+        });
+}
+
+/// The result of the game and the number of lines of the message.
+fn result_text(
+    game_score: &GameScore,
+    mode: GameMode,
+    difficulty: Difficulty,
+    save: &SaveData,
+) -> (String, usize) {
+    if let GameMode::Daily { day } = mode {
+        let result = save.daily.get(&day).cloned().unwrap_or_default();
+        let times = daily::wave_times_text(&result);
+        let message = format!(
+            "Daily challenge #{}: {}/{DAILY_WAVES} waves\n{times}",
+            daily::challenge_number(day),
+            result.wave_times_ms.len(),
+        );
+        let lines = 1 + times.lines().count();
+        return (message, lines);
+    }
+
+    let high_scores = save.high_scores(difficulty);
+    let message = match mode {
+        GameMode::Endless { seed } => format!(
+            "{} with seed {seed}.\nBest for this seed: {}",
+            cleared_text(game_score),
+            high_scores
+                .and_then(|scores| scores.endless.get(&seed))
+                .copied()
+                .unwrap_or_default()
+        ),
+        GameMode::Party { .. } => format!("{}.", cleared_text(game_score)),
+        GameMode::Campaign if game_score.win => {
+            "You've cleared all waves.\nCongratulations!".to_string()
+        }
+        GameMode::Campaign | GameMode::Daily { .. } | GameMode::Versus { .. } => format!(
+            "{}.\nBest: wave {}",
+            cleared_text(game_score),
+            high_scores.map_or(0, |scores| scores.campaign)
+        ),
+    };
+    (format!("{message}\nDifficulty: {}", difficulty.name()), 3)
+}
+
+/// The waves cleared in this run, without the ones that were skipped by
+/// starting at a later wave.
+fn cleared_text(game_score: &GameScore) -> String {
+    match game_score.waves_cleared() {
+        0 => "You didn't clear a wave".to_string(),
+        1 => format!("You've cleared 1 wave, up to wave {}", game_score.score),
+        cleared => format!(
+            "You've cleared {cleared} waves, up to wave {}",
+            game_score.score
+        ),
+    }
+}
+
+/// The scores of all players side by side, and the number of lines.
+fn versus_text(versus: &Versus) -> (String, usize) {
+    let mut lines = versus
+        .scores
+        .iter()
+        .enumerate()
+        .map(|(player, score)| {
+            format!(
+                "Player {}: {} points, {}/{VERSUS_ROUNDS} waves, {} misses",
+                player + 1,
+                score.points,
+                score.score,
+                score.misses
+            )
+        })
+        .collect::<Vec<_>>();
+    lines.push(match versus.winner() {
+        Some(player) => format!("Player {} wins!", player + 1),
+        None => "It's a tie!".to_string(),
+    });
+    let count = lines.len();
+    (lines.join("\n"), count)
+}
+
+/// The players ranked by their points.
+fn players_text(player_scores: &PlayerScores) -> String {
+    player_scores
+        .ranking()
+        .iter()
+        .enumerate()
+        .map(|(rank, (player, score))| {
+            format!(
+                "{}. {}: {} points, {} shots, {} misses",
+                rank + 1,
+                player.name(),
+                score.points,
+                score.shots,
+                score.misses
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn stats_text(game_score: &GameScore, stats: &RunStats) -> String {
+    let mut lines = vec![
+        format!("Points: {}", game_score.points),
+        format!(
+            "Shots: {}   Hits: {}   Misses: {}",
+            game_score.shots, game_score.hits, game_score.misses
+        ),
+        format!(
+            "Accuracy: {:.0}%",
+            stats::accuracy(game_score.hits, game_score.misses) * 100.0
+        ),
+    ];
+    for (creature, creature_stats) in &stats.creatures {
+        lines.push(format!(
+            "{creature:?}: {} hits, {} misses ({:.0}%)",
+            creature_stats.hits,
+            creature_stats.misses,
+            creature_stats.accuracy() * 100.0
+        ));
+    }
+    lines.join("\n")
+}
+
+fn waves_text(stats: &RunStats) -> String {
+    if stats.waves.is_empty() {
+        return "No wave played".to_string();
+    }
+    let skipped = stats.waves.len().saturating_sub(MAX_LISTED_WAVES);
+    let times = stats.waves[skipped..]
+        .iter()
+        .map(|wave| {
+            let lost = if wave.cleared { "" } else { " (lost)" };
+            format!("Wave {}: {:.1}s{lost}", wave.wave, wave.time_secs)
+        })
+        .collect::<Vec<_>>();
+    let mut text = times
+        .chunks(3)
+        .map(|line| line.join("   "))
+        .collect::<Vec<_>>()
+        .join("\n");
+    if skipped > 0 {
+        text = format!("Last {MAX_LISTED_WAVES} waves\n{text}");
+    }
+    text
+}
+
+fn spawn_heatmap(children: &mut ChildBuilder, stats: &RunStats) {
+    let (columns, rows) = HEATMAP_SIZE;
+    let cells = stats.heatmap(columns, rows);
+    let max = cells.iter().copied().max().unwrap_or(0).max(1);
+    children
+        .spawn((
+            Name::new("Heatmap"),
+            NodeBundle {
+                style: Style {
+                    width: Val::Px(columns as f32 * HEATMAP_CELL),
+                    height: Val::Px(rows as f32 * HEATMAP_CELL),
+                    flex_wrap: FlexWrap::Wrap,
+                    ..default()
+                },
+                background_color: BackgroundColor(THEME_VANILLA),
+                ..default()
+            },
+        ))
+        .with_children(|heatmap| {
+            for count in cells {
+                heatmap.spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(HEATMAP_CELL),
+                        height: Val::Px(HEATMAP_CELL),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(
+                        THEME_RED.with_alpha(count as f32 / max as f32),
+                    ),
+                    ..default()
+                });
+            }
+        });
+}
+
+fn select_tab(tab: ScoreTab) -> impl Fn(Trigger<OnPress>, ResMut<ScoreTab>) {
+    move |_trigger: Trigger<OnPress>, mut selected: ResMut<ScoreTab>| {
+        *selected = tab;
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn export_stats(
+    trigger: Trigger<OnPress>,
+    stats: Res<RunStats>,
+    game_score: Res<GameScore>,
+    children_query: Query<&Children>,
+    mut text_query: Query<&mut Text>,
+) {
+    let label = match stats.export(&game_score) {
+        Ok(dir) => {
+            info!("Exported run stats to {dir:?}");
+            "Exported"
+        }
+        Err(err) => {
+            warn!("Failed to export run stats: {err}");
+            "Export failed"
+        }
+    };
+    if let Ok(children) = children_query.get(trigger.entity()) {
+        set_child_text(children, &mut text_query, label);
+    }
+}
+
+fn enter_gameplay_screen(
+    _trigger: Trigger<OnPress>,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mode: Res<GameMode>,
+    mut versus: ResMut<Versus>,
+) {
+    if matches!(*mode, GameMode::Versus { .. }) {
+        // A rematch on the same waves.
+        *versus = Versus::default();
+        next_screen.set(Screen::Handoff);
+        return;
+    }
     next_screen.set(Screen::Gameplay);
 }
 
+fn copy_daily_result(
+    trigger: Trigger<OnPress>,
+    mode: Res<GameMode>,
+    save: Res<SaveData>,
+    children_query: Query<&Children>,
+    mut text_query: Query<&mut Text>,
+) {
+    let GameMode::Daily { day } = *mode else {
+        return;
+    };
+    let text = daily::share_text(day, &save.daily.get(&day).cloned().unwrap_or_default());
+    // The button tells whether it worked.
+    let label = match clipboard::copy(&text) {
+        Ok(()) => {
+            info!("Copied daily result to the clipboard");
+            "Copied"
+        }
+        Err(err) => {
+            warn!("Failed to copy daily result to the clipboard ({err}):\n{text}");
+            "Copy failed"
+        }
+    };
+    if let Ok(children) = children_query.get(trigger.entity()) {
+        set_child_text(children, &mut text_query, label);
+    }
+}
+
 fn enter_title_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
     next_screen.set(Screen::Title);
 }
@@ -0,0 +1,191 @@
+//! A settings screen that can be accessed from the title screen, to turn
+//! visual effects on and off and set up the custom difficulty.
+
+use bevy::prelude::*;
+
+use crate::{
+    demo::{
+        difficulty::{Difficulty, DifficultyModifiers},
+        juice::EffectSettings,
+    },
+    playfield::FillScreen,
+    save::SaveData,
+    screens::Screen,
+    theme::prelude::*,
+};
+
+use super::UiAssets;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Settings), spawn_settings_screen);
+}
+
+/// An effect that can be toggled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Effect {
+    ScreenShake,
+    HitStop,
+    Particles,
+    FloatingText,
+}
+
+impl Effect {
+    const ALL: [Effect; 4] = [
+        Effect::ScreenShake,
+        Effect::HitStop,
+        Effect::Particles,
+        Effect::FloatingText,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Effect::ScreenShake => "Screen shake",
+            Effect::HitStop => "Hit-stop",
+            Effect::Particles => "Particles",
+            Effect::FloatingText => "Score text",
+        }
+    }
+
+    fn enabled(self, settings: &mut EffectSettings) -> &mut bool {
+        match self {
+            Effect::ScreenShake => &mut settings.screen_shake,
+            Effect::HitStop => &mut settings.hit_stop,
+            Effect::Particles => &mut settings.particles,
+            Effect::FloatingText => &mut settings.floating_text,
+        }
+    }
+
+    fn label(self, mut settings: EffectSettings) -> String {
+        let state = if *self.enabled(&mut settings) {
+            "On"
+        } else {
+            "Off"
+        };
+        format!("{}: {state}", self.name())
+    }
+}
+
+/// A modifier of the custom difficulty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Modifier {
+    Speed,
+    WaveDuration,
+    CreatureScale,
+    Hitbox,
+    Balls,
+}
+
+/// The values the multipliers cycle through.
+const MULTIPLIERS: [f32; 7] = [0.5, 0.75, 0.85, 1.0, 1.25, 1.5, 2.0];
+
+const MAX_BALLS: u32 = 3;
+
+impl Modifier {
+    const ALL: [Modifier; 5] = [
+        Modifier::Speed,
+        Modifier::WaveDuration,
+        Modifier::CreatureScale,
+        Modifier::Hitbox,
+        Modifier::Balls,
+    ];
+
+    fn label(self, modifiers: DifficultyModifiers) -> String {
+        match self {
+            Modifier::Speed => format!("Speed: {}x", modifiers.speed),
+            Modifier::WaveDuration => format!("Wave time: {}x", modifiers.wave_duration),
+            Modifier::CreatureScale => format!("Creature size: {}x", modifiers.creature_scale),
+            Modifier::Hitbox => format!("Hitbox: {}x", modifiers.hitbox),
+            Modifier::Balls => format!("Balls: {}", modifiers.balls),
+        }
+    }
+
+    /// Change the modifier to its next value, starting over after the last.
+    fn cycle(self, modifiers: &mut DifficultyModifiers) {
+        let multiplier = match self {
+            Modifier::Speed => &mut modifiers.speed,
+            Modifier::WaveDuration => &mut modifiers.wave_duration,
+            Modifier::CreatureScale => &mut modifiers.creature_scale,
+            Modifier::Hitbox => &mut modifiers.hitbox,
+            Modifier::Balls => {
+                modifiers.balls = modifiers.balls % MAX_BALLS + 1;
+                return;
+            }
+        };
+        // Values from the save file don't have to be one of the steps.
+        *multiplier = MULTIPLIERS
+            .into_iter()
+            .find(|&value| value > *multiplier)
+            .unwrap_or(MULTIPLIERS[0]);
+    }
+}
+
+fn spawn_settings_screen(mut commands: Commands, assets: Res<UiAssets>, save: Res<SaveData>) {
+    commands.spawn((
+        SpriteBundle {
+            texture: assets.background.clone(),
+            ..Default::default()
+        },
+        FillScreen,
+        StateScoped(Screen::Settings),
+    ));
+
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Settings))
+        .with_children(|children| {
+            children.header("Effects", &assets);
+            for effect in Effect::ALL {
+                children
+                    .button(effect.label(save.effects))
+                    .observe(toggle_effect(effect));
+            }
+            children.header("Custom difficulty", &assets);
+            for modifier in Modifier::ALL {
+                children
+                    .button(modifier.label(save.custom_difficulty))
+                    .observe(cycle_modifier(modifier));
+            }
+            children.button("Back").observe(enter_title_screen);
+        });
+}
+
+fn toggle_effect(
+    effect: Effect,
+) -> impl Fn(Trigger<OnPress>, ResMut<SaveData>, Query<&Children>, Query<&mut Text>) {
+    move |trigger, mut save, children_query, mut text_query| {
+        let enabled = effect.enabled(&mut save.effects);
+        *enabled = !*enabled;
+        save.save();
+
+        if let Ok(children) = children_query.get(trigger.entity()) {
+            set_child_text(children, &mut text_query, effect.label(save.effects));
+        }
+    }
+}
+
+fn cycle_modifier(
+    modifier: Modifier,
+) -> impl Fn(Trigger<OnPress>, ResMut<SaveData>, ResMut<Difficulty>, Query<&Children>, Query<&mut Text>)
+{
+    move |trigger, mut save, mut difficulty, children_query, mut text_query| {
+        modifier.cycle(&mut save.custom_difficulty);
+        // Apply it right away if the custom difficulty is selected.
+        if let Difficulty::Custom(_) = save.difficulty {
+            save.difficulty = Difficulty::Custom(save.custom_difficulty);
+            *difficulty = save.difficulty;
+        }
+        save.save();
+
+        if let Ok(children) = children_query.get(trigger.entity()) {
+            set_child_text(
+                children,
+                &mut text_query,
+                modifier.label(save.custom_difficulty),
+            );
+        }
+    }
+}
+
+fn enter_title_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
@@ -2,17 +2,32 @@
 
 use bevy::prelude::*;
 
-use crate::{screens::Screen, theme::prelude::*};
+use crate::{
+    config::AppConfig,
+    demo::{
+        daily,
+        difficulty::Difficulty,
+        level::{GameMode, StartWave},
+        versus::Versus,
+    },
+    playfield::FillScreen,
+    save::{DailyResult, SaveData},
+    screens::Screen,
+    theme::prelude::*,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Title), spawn_title_screen);
 }
 
-fn spawn_title_screen(mut commands: Commands, assets: Res<UiAssets>) {
-    commands.spawn(SpriteBundle {
-        texture: assets.background.clone(),
-        ..Default::default()
-    });
+fn spawn_title_screen(mut commands: Commands, assets: Res<UiAssets>, difficulty: Res<Difficulty>) {
+    commands.spawn((
+        SpriteBundle {
+            texture: assets.background.clone(),
+            ..Default::default()
+        },
+        FillScreen,
+    ));
 
     commands
         .ui_root()
@@ -20,18 +35,153 @@ fn spawn_title_screen(mut commands: Commands, assets: Res<UiAssets>) {
         .with_children(|children| {
             children.large_message("Animal Arcade", &assets);
 
-            children.button("Play").observe(enter_gameplay_screen);
-            children.button("Credits").observe(enter_credits_screen);
+            children
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        column_gap: Val::Px(10.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|modes| {
+                    modes.button("Play").observe(enter_gameplay_screen);
+                    modes.button("Endless").observe(enter_endless_screen);
+                    modes.button("Daily").observe(enter_daily_challenge);
+                });
+            children
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        column_gap: Val::Px(10.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|options| {
+                    options.button("Levels").observe(enter_level_select_screen);
+                    options.button(difficulty.name()).observe(cycle_difficulty);
+                    options.button("Versus").observe(enter_versus_screen);
+                    options.button("Party").observe(enter_party_screen);
+                });
+            children
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        column_gap: Val::Px(10.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|extras| {
+                    extras
+                        .button("Achievements")
+                        .observe(enter_achievements_screen);
+                    extras.button("Settings").observe(enter_settings_screen);
+                    extras.button("Credits").observe(enter_credits_screen);
+                });
 
             #[cfg(not(target_family = "wasm"))]
             children.button("Exit").observe(exit_app);
         });
 }
 
-fn enter_gameplay_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+fn enter_gameplay_screen(
+    _trigger: Trigger<OnPress>,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut mode: ResMut<GameMode>,
+    mut start_wave: ResMut<StartWave>,
+    config: Res<AppConfig>,
+) {
+    *mode = GameMode::Campaign;
+    // Forget the wave picked on the level select screen.
+    *start_wave = StartWave::from_config(&config);
     next_screen.set(Screen::Gameplay);
 }
 
+fn enter_level_select_screen(
+    _trigger: Trigger<OnPress>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    next_screen.set(Screen::LevelSelect);
+}
+
+fn enter_endless_screen(
+    _trigger: Trigger<OnPress>,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut mode: ResMut<GameMode>,
+    config: Res<AppConfig>,
+) {
+    *mode = GameMode::Endless {
+        seed: config.seed.unwrap_or_else(rand::random),
+    };
+    next_screen.set(Screen::Gameplay);
+}
+
+fn enter_versus_screen(
+    _trigger: Trigger<OnPress>,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut mode: ResMut<GameMode>,
+    mut versus: ResMut<Versus>,
+    config: Res<AppConfig>,
+) {
+    *mode = GameMode::Versus {
+        seed: config.seed.unwrap_or_else(rand::random),
+    };
+    *versus = Versus::default();
+    next_screen.set(Screen::Handoff);
+}
+
+fn enter_party_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Party);
+}
+
+fn enter_daily_challenge(
+    _trigger: Trigger<OnPress>,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut mode: ResMut<GameMode>,
+    mut save: ResMut<SaveData>,
+) {
+    let day = daily::today();
+    *mode = GameMode::Daily { day };
+    if save.daily.contains_key(&day) {
+        // Already played today, show the result instead.
+        next_screen.set(Screen::Score);
+        return;
+    }
+    // Use up the attempt right away, in case the game is closed mid-challenge.
+    save.daily.insert(day, DailyResult::default());
+    save.save();
+    next_screen.set(Screen::Gameplay);
+}
+
+fn cycle_difficulty(
+    trigger: Trigger<OnPress>,
+    mut difficulty: ResMut<Difficulty>,
+    mut save: ResMut<SaveData>,
+    children_query: Query<&Children>,
+    mut text_query: Query<&mut Text>,
+) {
+    *difficulty = difficulty.next(save.custom_difficulty);
+    save.difficulty = *difficulty;
+    save.save();
+
+    if let Ok(children) = children_query.get(trigger.entity()) {
+        set_child_text(children, &mut text_query, difficulty.name());
+    }
+}
+
+fn enter_achievements_screen(
+    _trigger: Trigger<OnPress>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    next_screen.set(Screen::Achievements);
+}
+
+fn enter_settings_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Settings);
+}
+
 fn enter_credits_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
     next_screen.set(Screen::Credits);
 }
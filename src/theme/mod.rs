@@ -12,7 +12,7 @@ pub mod prelude {
     pub use super::{
         interaction::{InteractionPalette, OnPress},
         palette as ui_palette,
-        widgets::{Containers as _, Widgets as _},
+        widgets::{set_child_text, Containers as _, Widgets as _},
     };
 }
 
@@ -5,11 +5,29 @@ use bevy::{ecs::system::EntityCommands, prelude::*, ui::Val::*};
 use crate::screens::UiAssets;
 use crate::theme::{interaction::InteractionPalette, palette::*};
 
+/// Set the text of a widget like a button or header, that has its text in a
+/// child.
+pub fn set_child_text(
+    children: &Children,
+    text_query: &mut Query<&mut Text>,
+    value: impl Into<String>,
+) {
+    let value = value.into();
+    for &child in children {
+        if let Ok(mut text) = text_query.get_mut(child) {
+            text.sections[0].value.clone_from(&value);
+        }
+    }
+}
+
 /// An extension trait for spawning UI widgets.
 pub trait Widgets {
     /// Spawn a simple button with text.
     fn button(&mut self, text: impl Into<String>) -> EntityCommands;
 
+    /// Spawn a button with text that is smaller than [`Widgets::button`].
+    fn small_button(&mut self, text: impl Into<String>) -> EntityCommands;
+
     /// Spawn a simple header label. Bigger than [`Widgets::label`].
     fn header(&mut self, text: impl Into<String>, asset: &UiAssets) -> EntityCommands;
 
@@ -67,6 +85,49 @@ impl<T: Spawn> Widgets for T {
         entity
     }
 
+    fn small_button(&mut self, text: impl Into<String>) -> EntityCommands {
+        let mut entity = self.spawn((
+            Name::new("Small Button"),
+            ButtonBundle {
+                style: Style {
+                    min_width: Px(40.0),
+                    height: Px(40.0),
+                    padding: UiRect::horizontal(Px(8.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(THEME_VANILLA_DARK),
+                ..default()
+            },
+            InteractionPalette {
+                none: THEME_VANILLA_DARK,
+                hovered: THEME_VANILLA,
+                pressed: THEME_RED_DARK,
+            },
+        ));
+        entity.with_children(|children| {
+            children.spawn((
+                Name::new("Button Text"),
+                TextBundle::from_section(
+                    text,
+                    TextStyle {
+                        font_size: 24.0,
+                        color: THEME_VANILLA,
+                        ..default()
+                    },
+                ),
+                InteractionPalette {
+                    none: THEME_VANILLA,
+                    hovered: THEME_VANILLA_DARK,
+                    pressed: THEME_VANILLA_DARK,
+                },
+            ));
+        });
+
+        entity
+    }
+
     fn header(&mut self, text: impl Into<String>, asset: &UiAssets) -> EntityCommands {
         let mut entity = self.spawn((
             Name::new("Header"),